@@ -1,8 +1,12 @@
 use std::collections::HashMap;
 use std::fmt;
+use std::hash::Hash;
 use std::ops::Range;
 
-use ariadne::{Color, ColorGenerator, Fmt, Label, Report, ReportKind, Source};
+use ariadne::{
+    sources, Cache, CharSet, Color, ColorGenerator, Config, Fmt, Label, Report, ReportBuilder,
+    ReportKind, Source,
+};
 
 /// A source file with name and content
 pub struct SourceFile {
@@ -37,6 +41,24 @@ impl SourceManager {
             .get(file)
             .map(|f| Source::from(f.content.clone()))
     }
+
+    /// Render `diagnostic` against every registered file, so labels may land
+    /// in files other than `file` (e.g. each module of a dependency cycle).
+    pub fn report_with_sources(&self, diagnostic: &CompilerDiagnostic, file: &str) -> String {
+        let cache = sources(
+            self.files
+                .values()
+                .map(|f| (f.name.clone(), f.content.clone())),
+        );
+        let mut buffer = Vec::new();
+        // Writing into a Vec<u8> cannot fail; labels in unregistered files
+        // are skipped by ariadne (with a note on stderr) rather than erroring
+        diagnostic
+            .to_multi_file_report(file)
+            .write(cache, &mut buffer)
+            .unwrap();
+        strip_ansi(&String::from_utf8_lossy(&buffer))
+    }
 }
 
 impl Default for SourceManager {
@@ -83,7 +105,7 @@ impl fmt::Display for Type {
 }
 
 /// Compiler diagnostics with rich information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum CompilerDiagnostic {
     TypeError {
         expected: Type,
@@ -117,7 +139,53 @@ pub enum CompilerDiagnostic {
 }
 
 impl CompilerDiagnostic {
-    pub fn to_report(&self, _file_id: &str) -> Report<'static, (&'static str, Range<usize>)> {
+    /// Build an `UnresolvedName` diagnostic, suggesting the names in `scope`
+    /// that are within a couple of edits of `name`.
+    pub fn unresolved_name(name: &str, span: Range<usize>, scope: &[String]) -> Self {
+        CompilerDiagnostic::UnresolvedName {
+            name: name.to_string(),
+            span,
+            similar_names: suggest_names(name, scope, 3),
+            imported_modules: vec![],
+        }
+    }
+
+    pub fn to_report(&self, file_id: &str) -> Report<'static, (&'static str, Range<usize>)> {
+        self.to_report_with_kind(ReportKind::Error, file_id)
+    }
+
+    /// Like `to_report`, but with a caller-chosen severity so lint-style
+    /// diagnostics can be rendered as warnings
+    pub fn to_report_with_kind(
+        &self,
+        kind: ReportKind<'static>,
+        _file_id: &str,
+    ) -> Report<'static, (&'static str, Range<usize>)> {
+        self.build_report(kind, "file", |_| "module")
+    }
+
+    /// Build a report whose spans point into real files: `file` for the
+    /// diagnostic's own spans, and each module name for `CyclicDependency`.
+    pub fn to_multi_file_report(&self, file: &str) -> Report<'static, (String, Range<usize>)> {
+        self.build_report(ReportKind::Error, file.to_string(), |module| {
+            module.to_string()
+        })
+    }
+
+    fn build_report<Id>(
+        &self,
+        kind: ReportKind<'static>,
+        file: Id,
+        module_file: impl Fn(&str) -> Id,
+    ) -> Report<'static, (Id, Range<usize>)>
+    where
+        Id: Clone + fmt::Debug + Hash + Eq, {
+        // Warnings use yellow for their primary labels, matching warning_report
+        let primary = match kind {
+            ReportKind::Warning => Color::Yellow,
+            _ => Color::Red,
+        };
+
         match self {
             CompilerDiagnostic::TypeError {
                 expected,
@@ -126,21 +194,21 @@ impl CompilerDiagnostic {
                 expected_span,
                 context,
             } => {
-                let mut report = Report::build(ReportKind::Error, ("file", expr_span.clone()))
+                let mut report = Report::build(kind, (file.clone(), expr_span.clone()))
                     .with_message(format!("Type mismatch in {}", context))
                     .with_label(
-                        Label::new(("file", expr_span.clone()))
+                        Label::new((file.clone(), expr_span.clone()))
                             .with_message(format!(
                                 "Expected {}, found {}",
                                 expected.to_string().fg(Color::Green),
-                                found.to_string().fg(Color::Red)
+                                found.to_string().fg(primary)
                             ))
-                            .with_color(Color::Red),
+                            .with_color(primary),
                     );
 
                 if let Some(expected_span) = expected_span {
                     report = report.with_label(
-                        Label::new(("file", expected_span.clone()))
+                        Label::new((file.clone(), expected_span.clone()))
                             .with_message("Expected because of this")
                             .with_color(Color::Blue),
                     );
@@ -149,7 +217,7 @@ impl CompilerDiagnostic {
                 report
                     .with_note(format!(
                         "Cannot convert {} to {}",
-                        found.to_string().fg(Color::Red),
+                        found.to_string().fg(primary),
                         expected.to_string().fg(Color::Green)
                     ))
                     .finish()
@@ -161,12 +229,12 @@ impl CompilerDiagnostic {
                 similar_names,
                 imported_modules,
             } => {
-                let mut report = Report::build(ReportKind::Error, ("file", span.clone()))
+                let mut report = Report::build(kind, (file.clone(), span.clone()))
                     .with_message(format!("Cannot find '{}' in scope", name))
                     .with_label(
-                        Label::new(("file", span.clone()))
+                        Label::new((file.clone(), span.clone()))
                             .with_message("Not found")
-                            .with_color(Color::Red),
+                            .with_color(primary),
                     );
 
                 if !similar_names.is_empty() {
@@ -194,12 +262,12 @@ impl CompilerDiagnostic {
                 expected,
                 note,
             } => {
-                let mut report = Report::build(ReportKind::Error, ("file", span.clone()))
+                let mut report = Report::build(kind, (file.clone(), span.clone()))
                     .with_message("Syntax error")
                     .with_label(
-                        Label::new(("file", span.clone()))
+                        Label::new((file.clone(), span.clone()))
                             .with_message(message)
-                            .with_color(Color::Red),
+                            .with_color(primary),
                     );
 
                 if !expected.is_empty() {
@@ -239,15 +307,15 @@ impl CompilerDiagnostic {
                     ("immutable", Color::Blue)
                 };
 
-                Report::build(ReportKind::Error, ("file", second_borrow.clone()))
+                Report::build(kind, (file.clone(), second_borrow.clone()))
                     .with_message(format!("Cannot borrow '{}' as {}", var_name, second_kind))
                     .with_label(
-                        Label::new(("file", first_borrow.clone()))
+                        Label::new((file.clone(), first_borrow.clone()))
                             .with_message(format!("First {} borrow occurs here", first_kind))
                             .with_color(first_color),
                     )
                     .with_label(
-                        Label::new(("file", second_borrow.clone()))
+                        Label::new((file.clone(), second_borrow.clone()))
                             .with_message(format!(
                                 "Second {} borrow occurs here",
                                 second_kind
@@ -260,14 +328,15 @@ impl CompilerDiagnostic {
 
             CompilerDiagnostic::CyclicDependency { modules } => {
                 let mut colors = ColorGenerator::new();
-                let mut report = Report::build(ReportKind::Error, ("module", modules[0].1.clone()))
-                    .with_message("Cyclic module dependency detected");
+                let mut report =
+                    Report::build(kind, (module_file(&modules[0].0), modules[0].1.clone()))
+                        .with_message("Cyclic module dependency detected");
 
                 for (i, (module, span)) in modules.iter().enumerate() {
                     let color = colors.next();
                     let next_module = &modules[(i + 1) % modules.len()].0;
                     report = report.with_label(
-                        Label::new(("module", span.clone()))
+                        Label::new((module_file(module), span.clone()))
                             .with_message(format!("'{}' imports '{}'", module, next_module))
                             .with_color(color),
                     );
@@ -281,6 +350,56 @@ impl CompilerDiagnostic {
     }
 }
 
+/// Maximum edit distance for a candidate to count as a suggestion
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Suggest up to `max` candidates close to `target`, nearest first
+pub fn suggest_names(target: &str, candidates: &[String], max: usize) -> Vec<String> {
+    let mut scored: Vec<(usize, &String)> = candidates
+        .iter()
+        .filter(|c| c.as_str() != target)
+        .map(|c| (levenshtein(target, c), c))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .collect();
+    scored.sort();
+    scored.dedup();
+    scored
+        .into_iter()
+        .take(max)
+        .map(|(_, c)| c.clone())
+        .collect()
+}
+
+/// Levenshtein distance extended with adjacent transpositions (optimal string
+/// alignment), so a swapped pair like `lenght`/`length` costs one edit
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut dist = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in dist.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in dist[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (dist[i - 1][j] + 1)
+                .min(dist[i][j - 1] + 1)
+                .min(dist[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(dist[i - 2][j - 2] + 1);
+            }
+            dist[i][j] = best;
+        }
+    }
+
+    dist[a.len()][b.len()]
+}
+
 /// Language server protocol-style diagnostics
 pub struct LspDiagnostic {
     pub severity: DiagnosticSeverity,
@@ -357,6 +476,149 @@ pub fn to_lsp_diagnostic(diagnostic: &CompilerDiagnostic, _file: &str) -> LspDia
     }
 }
 
+impl DiagnosticSeverity {
+    /// The numeric severity used on the LSP wire
+    pub fn lsp_code(self) -> u8 {
+        match self {
+            DiagnosticSeverity::Error => 1,
+            DiagnosticSeverity::Warning => 2,
+            DiagnosticSeverity::Information => 3,
+            DiagnosticSeverity::Hint => 4,
+        }
+    }
+}
+
+impl DiagnosticTag {
+    /// The numeric tag used on the LSP wire
+    pub fn lsp_code(self) -> u8 {
+        match self {
+            DiagnosticTag::Unnecessary => 1,
+            DiagnosticTag::Deprecated => 2,
+        }
+    }
+}
+
+/// Serialize to the JSON shape of an LSP `Diagnostic`, where `range` is a
+/// byte range in `file`. Byte ranges are converted to LSP line/character
+/// positions (UTF-16 code units) using the text registered in `sources`, so
+/// every file the diagnostic mentions must be registered there. Related
+/// locations are given `file://` URIs, with relative file names resolved
+/// against the current directory.
+pub fn lsp_to_json(
+    diag: &LspDiagnostic,
+    file: &str,
+    range: Range<usize>,
+    sources: &SourceManager,
+) -> Result<String, String> {
+    let text = |name: &str| {
+        sources
+            .files
+            .get(name)
+            .map(|f| f.content.as_str())
+            .ok_or_else(|| format!("unknown file: {}", name))
+    };
+
+    let mut fields = vec![
+        format!("\"range\":{}", json_range(text(file)?, &range)),
+        format!("\"severity\":{}", diag.severity.lsp_code()),
+    ];
+    if let Some(code) = &diag.code {
+        fields.push(format!("\"code\":{}", json_string(code)));
+    }
+    fields.push(format!("\"message\":{}", json_string(&diag.message)));
+
+    if !diag.related_information.is_empty() {
+        let related = diag
+            .related_information
+            .iter()
+            .map(|info| {
+                let (name, span) = &info.location;
+                Ok(format!(
+                    "{{\"location\":{{\"uri\":{},\"range\":{}}},\"message\":{}}}",
+                    json_string(&file_uri(name)?),
+                    json_range(text(name)?, span),
+                    json_string(&info.message)
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()?
+            .join(",");
+        fields.push(format!("\"relatedInformation\":[{}]", related));
+    }
+
+    if !diag.tags.is_empty() {
+        let tags = diag
+            .tags
+            .iter()
+            .map(|tag| tag.lsp_code().to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        fields.push(format!("\"tags\":[{}]", tags));
+    }
+
+    Ok(format!("{{{}}}", fields.join(",")))
+}
+
+fn file_uri(name: &str) -> Result<String, String> {
+    let path = std::path::absolute(name).map_err(|e| format!("{}: {}", name, e))?;
+    let mut uri = String::from("file://");
+    for segment in path.iter().skip(1) {
+        uri.push('/');
+        for &b in segment.to_string_lossy().as_bytes() {
+            match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                    uri.push(b as char)
+                }
+                _ => uri.push_str(&format!("%{:02X}", b)),
+            }
+        }
+    }
+    Ok(uri)
+}
+
+fn json_range(text: &str, range: &Range<usize>) -> String {
+    format!(
+        "{{\"start\":{},\"end\":{}}}",
+        json_position(text, range.start),
+        json_position(text, range.end)
+    )
+}
+
+// LSP positions count lines from 0 and characters in UTF-16 code units from
+// the start of the line. Offsets past the end are clamped to it.
+fn json_position(text: &str, offset: usize) -> String {
+    let offset = offset.min(text.len());
+    let before = &text.as_bytes()[..offset];
+    let line = before.iter().filter(|&&b| b == b'\n').count();
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    let character: usize = text[line_start..]
+        .char_indices()
+        .take_while(|(i, _)| line_start + i < offset)
+        .map(|(_, c)| c.len_utf16())
+        .sum();
+    format!("{{\"line\":{},\"character\":{}}}", line, character)
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
 /// Helper function to create error reports
 pub fn error_report(
     _file: &str,
@@ -374,6 +636,31 @@ pub fn error_report(
         .finish()
 }
 
+/// Helper function to create error reports with a secondary "defined here"
+/// style label alongside the primary one
+pub fn error_report_with_secondary(
+    _file: &str,
+    primary: Range<usize>,
+    secondary: Range<usize>,
+    message: &str,
+    primary_msg: &str,
+    secondary_msg: &str,
+) -> Report<'static, (&'static str, Range<usize>)> {
+    Report::build(ReportKind::Error, ("static", primary.clone()))
+        .with_message(message)
+        .with_label(
+            Label::new(("static", primary))
+                .with_message(primary_msg)
+                .with_color(Color::Red),
+        )
+        .with_label(
+            Label::new(("static", secondary))
+                .with_message(secondary_msg)
+                .with_color(Color::Blue),
+        )
+        .finish()
+}
+
 /// Helper function to create warning reports
 pub fn warning_report(
     _file: &str,
@@ -391,6 +678,84 @@ pub fn warning_report(
         .finish()
 }
 
+/// Cache that serves the same source for every id, since the reports in this
+/// module label their spans with placeholder ids like "file" and "static".
+struct SingleSource(Source<String>);
+
+impl Cache<&'static str> for SingleSource {
+    type Storage = String;
+
+    fn fetch(&mut self, _id: &&'static str) -> Result<&Source<String>, impl fmt::Debug> {
+        Ok::<_, ()>(&self.0)
+    }
+
+    fn display<'a>(&self, id: &'a &'static str) -> Option<impl fmt::Display + 'a> {
+        Some(*id)
+    }
+}
+
+/// Render a report to a plain string with colors stripped, for snapshot tests
+/// and logging.
+pub fn render_report(
+    report: Report<'static, (&'static str, Range<usize>)>,
+    source: &str,
+) -> String {
+    let mut buffer = Vec::new();
+    // Writing into a Vec<u8> cannot fail
+    report
+        .write(SingleSource(Source::from(source.to_string())), &mut buffer)
+        .unwrap();
+    strip_ansi(&String::from_utf8_lossy(&buffer))
+}
+
+/// Like `render_report`, but drawn with plain ASCII (`|`, `-`, `^`) instead
+/// of box-drawing characters, for terminals and log files that garble them.
+/// ariadne fixes a report's config when it is finished, so this takes the
+/// unfinished builder.
+pub fn render_report_ascii(
+    report: ReportBuilder<'static, (&'static str, Range<usize>)>,
+    source: &str,
+) -> String {
+    let config = Config::default()
+        .with_char_set(CharSet::Ascii)
+        .with_color(false);
+    render_report(report.with_config(config).finish(), source)
+}
+
+/// Render every diagnostic in `diags` against `source`, one report after
+/// another. Diagnostics that compare equal (same variant, spans and message)
+/// are rendered only once, keeping the first occurrence's position.
+pub fn render_diagnostics(diags: &[CompilerDiagnostic], source: &str) -> String {
+    let mut unique: Vec<&CompilerDiagnostic> = Vec::new();
+    for diag in diags {
+        if !unique.contains(&diag) {
+            unique.push(diag);
+        }
+    }
+    unique
+        .into_iter()
+        .map(|diag| render_report(diag.to_report("file"), source))
+        .collect()
+}
+
+/// Remove ANSI color sequences (`ESC [ ... m`) from rendered output
+fn strip_ansi(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -419,4 +784,213 @@ mod tests {
         // Just ensure it builds without panic
         let _ = format!("{:?}", report);
     }
+
+    #[test]
+    fn test_render_report() {
+        let source = "let x = (1 + 2;";
+        let diagnostic = CompilerDiagnostic::SyntaxError {
+            message: "Unclosed delimiter".to_string(),
+            span: 8..9,
+            expected: vec![")".to_string()],
+            note: None,
+        };
+
+        let output = render_report(diagnostic.to_report("main.rs"), source);
+        assert!(output.contains("Syntax error"));
+        assert!(output.contains("Unclosed delimiter"));
+        assert!(output.contains(source));
+        assert!(output.lines().any(|line| line.contains('┬')));
+        assert!(!output.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_render_diagnostics_dedup() {
+        let source = "let n = lenght + 1;";
+        let scope = vec!["length".to_string()];
+        let unresolved = CompilerDiagnostic::unresolved_name("lenght", 8..14, &scope);
+        let syntax = CompilerDiagnostic::SyntaxError {
+            message: "Unexpected token".to_string(),
+            span: 16..17,
+            expected: vec![],
+            note: None,
+        };
+        let diags = vec![unresolved.clone(), syntax, unresolved];
+
+        let output = render_diagnostics(&diags, source);
+        assert_eq!(output.matches("Cannot find 'lenght' in scope").count(), 1);
+        assert_eq!(output.matches("Unexpected token").count(), 1);
+        assert!(output.find("Cannot find").unwrap() < output.find("Unexpected token").unwrap());
+    }
+
+    #[test]
+    fn test_error_report_with_secondary() {
+        let source = "let count = 1;\ncount = 2;";
+        let report = error_report_with_secondary(
+            "main.rs",
+            15..20,
+            4..9,
+            "Cannot assign twice to immutable variable",
+            "reassigned here",
+            "First assignment here",
+        );
+
+        let output = render_report(report, source);
+        assert!(output.contains("Cannot assign twice to immutable variable"));
+        assert!(output.contains("reassigned here"));
+        assert!(output.contains("First assignment here"));
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("lenght", "length"), 1);
+        assert_eq!(levenshtein("lenght", "height"), 2);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("same", "same"), 0);
+    }
+
+    #[test]
+    fn test_suggest_names() {
+        let scope: Vec<String> = ["length", "foo", "lengths", "width", "height"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let suggestions = suggest_names("lenght", &scope, 5);
+        assert_eq!(suggestions[0], "length");
+        assert!(!suggestions.contains(&"foo".to_string()));
+        assert!(!suggestions.contains(&"width".to_string()));
+
+        assert_eq!(suggest_names("lenght", &scope, 1), vec!["length"]);
+        assert!(suggest_names("xyzzy", &scope, 5).is_empty());
+
+        match CompilerDiagnostic::unresolved_name("lenght", 0..6, &scope) {
+            CompilerDiagnostic::UnresolvedName { similar_names, .. } => {
+                assert_eq!(similar_names, vec!["length", "height", "lengths"]);
+            }
+            _ => panic!("Expected UnresolvedName"),
+        }
+    }
+
+    #[test]
+    fn test_cross_file_report() {
+        let mut manager = SourceManager::new();
+        manager.add_file("parser.rs".to_string(), "use lexer::Token;".to_string());
+        manager.add_file("lexer.rs".to_string(), "use parser::Ast;".to_string());
+
+        let diagnostic = CompilerDiagnostic::CyclicDependency {
+            modules: vec![
+                ("parser.rs".to_string(), 4..9),
+                ("lexer.rs".to_string(), 4..10),
+            ],
+        };
+
+        let output = manager.report_with_sources(&diagnostic, "parser.rs");
+        assert!(output.contains("Cyclic module dependency detected"));
+        assert!(output.contains("parser.rs"));
+        assert!(output.contains("lexer.rs"));
+        assert!(output.contains("use lexer::Token;"));
+        assert!(output.contains("use parser::Ast;"));
+        assert!(output.contains("'parser.rs' imports 'lexer.rs'"));
+        assert!(output.contains("'lexer.rs' imports 'parser.rs'"));
+    }
+
+    #[test]
+    fn test_report_with_kind() {
+        let source = "let x: int = \"hello\";";
+        let diagnostic = CompilerDiagnostic::TypeError {
+            expected: Type::Int,
+            found: Type::String,
+            expr_span: 13..20,
+            expected_span: Some(7..10),
+            context: "let binding".to_string(),
+        };
+
+        let error = render_report(diagnostic.to_report("main.rs"), source);
+        let warning = render_report(
+            diagnostic.to_report_with_kind(ReportKind::Warning, "main.rs"),
+            source,
+        );
+
+        assert!(error.starts_with("Error: Type mismatch in let binding"));
+        assert!(warning.starts_with("Warning: Type mismatch in let binding"));
+        assert_ne!(error, warning);
+    }
+
+    #[test]
+    fn test_lsp_to_json() {
+        let mut sources = SourceManager::new();
+        // The emoji is four bytes but two UTF-16 code units
+        sources.add_file(
+            "main.rs".to_string(),
+            "let x = 1;\nlet \u{1F600} = cout;".to_string(),
+        );
+        sources.add_file("/src/a.rs".to_string(), "mod b;\n".to_string());
+        sources.add_file("/src/my mod/b.rs".to_string(), "use a;\nmod a;".to_string());
+
+        let diagnostic = CompilerDiagnostic::unresolved_name("cout", 22..26, &[]);
+        let lsp = to_lsp_diagnostic(&diagnostic, "main.rs");
+        let json = lsp_to_json(&lsp, "main.rs", 22..26, &sources).unwrap();
+
+        assert!(json.contains("\"severity\":1"));
+        assert!(json.contains("\"code\":\"E0425\""));
+        assert!(json.contains("\"message\":\"Cannot find 'cout' in scope\""));
+        assert!(json.contains(
+            "\"range\":{\"start\":{\"line\":1,\"character\":9},\"end\":{\"line\":1,\"character\":13}}"
+        ));
+
+        let cycle = CompilerDiagnostic::CyclicDependency {
+            modules: vec![
+                ("/src/a.rs".to_string(), 0..5),
+                ("/src/my mod/b.rs".to_string(), 2..7),
+            ],
+        };
+        let mut lsp = to_lsp_diagnostic(&cycle, "/src/a.rs");
+        lsp.severity = DiagnosticSeverity::Hint;
+        lsp.tags.push(DiagnosticTag::Unnecessary);
+        let json = lsp_to_json(&lsp, "/src/a.rs", 0..5, &sources).unwrap();
+
+        assert!(json.contains("\"severity\":4"));
+        assert!(json.contains(
+            "{\"location\":{\"uri\":\"file:///src/my%20mod/b.rs\",\"range\":{\"start\":{\"line\":0,\"character\":2},\"end\":{\"line\":1,\"character\":0}}},\"message\":\"Module '/src/my mod/b.rs' is part of the cycle\"}"
+        ));
+        assert!(json.contains("\"tags\":[1]"));
+
+        let err = lsp_to_json(&lsp, "c.rs", 0..1, &sources).unwrap_err();
+        assert_eq!(err, "unknown file: c.rs");
+
+        // Relative names are resolved against the current directory
+        let uri = file_uri("c.rs").unwrap();
+        assert!(uri.starts_with("file:///") && uri.ends_with("/c.rs"));
+    }
+
+    #[test]
+    fn test_json_string_escaping() {
+        assert_eq!(json_string("say \"hi\"\n"), r#""say \"hi\"\n""#);
+        assert_eq!(json_string("a\\b\u{1}"), r#""a\\b\u0001""#);
+    }
+
+    #[test]
+    fn test_render_report_ascii() {
+        let source = "let x: int = \"hello\";";
+        let report = Report::build(ReportKind::Error, ("main.rs", 13..20))
+            .with_message("Type mismatch")
+            .with_label(
+                Label::new(("main.rs", 13..20))
+                    .with_message("Expected int, found string")
+                    .with_color(Color::Red),
+            );
+
+        let output = render_report_ascii(report, source);
+        // Only the echoed source line may contain non-ASCII text
+        let drawing: String = output
+            .lines()
+            .filter(|line| !line.contains(source))
+            .collect();
+        assert!(drawing.is_ascii(), "{}", output);
+        assert!(output.contains("Expected int, found string"));
+        assert!(output.contains('|'));
+        assert!(output.contains('^'));
+        assert!(!output.contains('─'));
+    }
 }
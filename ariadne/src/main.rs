@@ -1,5 +1,7 @@
 use ariadne::{Color, ColorGenerator, Label, Report, ReportKind, Source};
-use ariadne_example::{warning_report, CompilerDiagnostic, SourceManager, Type};
+use ariadne_example::{
+    error_report_with_secondary, warning_report, CompilerDiagnostic, SourceManager, Type,
+};
 
 fn main() {
     println!("=== Basic Type Error Reporting ===");
@@ -116,18 +118,25 @@ fn demonstrate_borrow_error() {
 }
 
 fn demonstrate_cyclic_dependency() {
-    // Simulate multiple files
-    let module_a = r#"use module_b::helper;
+    let mut manager = SourceManager::new();
+    manager.add_file(
+        "module_a.rs".to_string(),
+        r#"use module_b::helper;
 
 pub fn process() {
     helper();
-}"#;
-
-    let _module_b = r#"use module_a::process;
+}"#
+        .to_string(),
+    );
+    manager.add_file(
+        "module_b.rs".to_string(),
+        r#"use module_a::process;
 
 pub fn helper() {
     process();
-}"#;
+}"#
+        .to_string(),
+    );
 
     let diagnostic = CompilerDiagnostic::CyclicDependency {
         modules: vec![
@@ -136,11 +145,11 @@ pub fn helper() {
         ],
     };
 
-    // This would normally print across multiple files
-    diagnostic
-        .to_report("module_a.rs")
-        .eprint(("module", Source::from(module_a)))
-        .unwrap();
+    // Each label is rendered against its own file
+    eprint!(
+        "{}",
+        manager.report_with_sources(&diagnostic, "module_a.rs")
+    );
 }
 
 fn demonstrate_multi_file_error() {
@@ -208,6 +217,17 @@ fn demonstrate_helper_functions() {
     );
 
     warning.eprint(("static", Source::from(source))).unwrap();
+
+    let error = error_report_with_secondary(
+        "math.rs",
+        103..104,
+        18..24,
+        "Division uses a parameter that may be zero",
+        "Divisor used here",
+        "Parameter declared here",
+    );
+
+    error.eprint(("static", Source::from(source))).unwrap();
 }
 
 fn demonstrate_complex_report() {
@@ -1,14 +1,19 @@
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::ops::Range;
 
-use codespan_reporting::diagnostic::{Diagnostic, Label};
-use codespan_reporting::files::SimpleFiles;
+use codespan_reporting::diagnostic::{Diagnostic, Label, LabelStyle, Severity};
+use codespan_reporting::files::{Files, SimpleFiles};
 use codespan_reporting::term::{self, Config};
-use termcolor::{ColorChoice, StandardStream};
+use termcolor::{ColorChoice, NoColor, StandardStream, WriteColor};
 
 /// A compiler diagnostic system built on codespan-reporting
 pub struct DiagnosticEngine {
     files: SimpleFiles<String, String>,
     config: Config,
+    min_severity: Severity,
+    errors: Cell<usize>,
+    warnings: Cell<usize>,
 }
 
 impl DiagnosticEngine {
@@ -16,17 +21,162 @@ impl DiagnosticEngine {
         Self {
             files: SimpleFiles::new(),
             config: Config::default(),
+            min_severity: Severity::Help,
+            errors: Cell::new(0),
+            warnings: Cell::new(0),
         }
     }
 
+    /// Suppress diagnostics less severe than `severity` when emitting or
+    /// rendering, e.g. `Severity::Error` for a quiet mode
+    pub fn with_min_severity(mut self, severity: Severity) -> Self {
+        self.min_severity = severity;
+        self
+    }
+
+    /// Number of errors (including bugs) emitted or rendered so far
+    pub fn error_count(&self) -> usize {
+        self.errors.get()
+    }
+
+    /// Number of warnings emitted or rendered so far
+    pub fn warning_count(&self) -> usize {
+        self.warnings.get()
+    }
+
     pub fn add_file(&mut self, name: String, source: String) -> usize {
         self.files.add(name, source)
     }
 
     pub fn emit_diagnostic(&self, diagnostic: Diagnostic<usize>) {
         let writer = StandardStream::stderr(ColorChoice::Always);
-        let _ =
-            term::emit_to_write_style(&mut writer.lock(), &self.config, &self.files, &diagnostic);
+        self.write_diagnostic(&mut writer.lock(), &diagnostic);
+    }
+
+    /// Render a diagnostic without color codes, for tests and logs
+    pub fn render(&self, diagnostic: &Diagnostic<usize>) -> String {
+        let mut buffer = NoColor::new(Vec::new());
+        self.write_diagnostic(&mut buffer, diagnostic);
+        String::from_utf8_lossy(&buffer.into_inner()).into_owned()
+    }
+
+    /// Emit several diagnostics ordered by file and then by the position of
+    /// their earliest primary label, rather than in insertion order
+    pub fn emit_sorted(&self, diagnostics: &[Diagnostic<usize>]) {
+        let writer = StandardStream::stderr(ColorChoice::Always);
+        let mut writer = writer.lock();
+        for diagnostic in sort_by_position(diagnostics) {
+            self.write_diagnostic(&mut writer, diagnostic);
+        }
+    }
+
+    /// Render several diagnostics in the same order as `emit_sorted`
+    pub fn render_sorted(&self, diagnostics: &[Diagnostic<usize>]) -> String {
+        sort_by_position(diagnostics)
+            .into_iter()
+            .map(|diagnostic| self.render(diagnostic))
+            .collect()
+    }
+
+    // Every output path goes through here so the severity threshold and the
+    // counters apply uniformly.
+    fn write_diagnostic(&self, writer: &mut dyn WriteColor, diagnostic: &Diagnostic<usize>) {
+        if diagnostic.severity < self.min_severity {
+            return;
+        }
+
+        match diagnostic.severity {
+            Severity::Bug | Severity::Error => self.errors.set(self.errors.get() + 1),
+            Severity::Warning => self.warnings.set(self.warnings.get() + 1),
+            Severity::Note | Severity::Help => {}
+        }
+
+        let _ = term::emit_to_write_style(writer, &self.config, &self.files, diagnostic);
+    }
+
+    /// Serialize a diagnostic to JSON, resolving label files to their names
+    ///
+    /// The shape is `{"severity", "message", "labels": [{"file", "start",
+    /// "end", "style", "message"}], "notes"}` with byte offsets for ranges.
+    pub fn emit_json(&self, diagnostic: &Diagnostic<usize>) -> String {
+        let severity = match diagnostic.severity {
+            Severity::Bug => "bug",
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+            Severity::Help => "help",
+        };
+
+        let labels: Vec<String> = diagnostic
+            .labels
+            .iter()
+            .map(|label| {
+                let file = self.files.name(label.file_id).unwrap_or_default();
+                let style = match label.style {
+                    LabelStyle::Primary => "primary",
+                    LabelStyle::Secondary => "secondary",
+                };
+                format!(
+                    r#"{{"file":{},"start":{},"end":{},"style":"{}","message":{}}}"#,
+                    json_string(&file),
+                    label.range.start,
+                    label.range.end,
+                    style,
+                    json_string(&label.message)
+                )
+            })
+            .collect();
+
+        let notes: Vec<String> = diagnostic
+            .notes
+            .iter()
+            .map(|note| json_string(note))
+            .collect();
+
+        format!(
+            r#"{{"severity":"{}","message":{},"labels":[{}],"notes":[{}]}}"#,
+            severity,
+            json_string(&diagnostic.message),
+            labels.join(","),
+            notes.join(",")
+        )
+    }
+
+    /// Format a diagnostic as a GitHub Actions workflow command, e.g.
+    /// `::error file=main.ml,line=1,col=9::type mismatch`
+    ///
+    /// The position is the 1-based line and column of the first primary
+    /// label's start; without one, only the message is emitted. Warnings map
+    /// to `::warning` and notes and help to `::notice`.
+    pub fn to_github_annotation(&self, diagnostic: &Diagnostic<usize>) -> String {
+        let command = match diagnostic.severity {
+            Severity::Bug | Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note | Severity::Help => "notice",
+        };
+
+        let position = diagnostic
+            .labels
+            .iter()
+            .find(|label| label.style == LabelStyle::Primary)
+            .and_then(|label| {
+                let name = self.files.name(label.file_id).ok()?;
+                let location = self.files.location(label.file_id, label.range.start).ok()?;
+                Some(format!(
+                    " file={},line={},col={}",
+                    github_escape_property(&name),
+                    location.line_number,
+                    location.column_number
+                ))
+            })
+            .unwrap_or_default();
+
+        format!(
+            "::{}{}::{}",
+            command,
+            position,
+            github_escape_data(&diagnostic.message)
+        )
     }
 }
 
@@ -36,6 +186,54 @@ impl Default for DiagnosticEngine {
     }
 }
 
+// Diagnostics without a primary label sort after all positioned ones; the
+// sort is stable so ties keep their insertion order.
+fn sort_by_position(diagnostics: &[Diagnostic<usize>]) -> Vec<&Diagnostic<usize>> {
+    let mut sorted: Vec<_> = diagnostics.iter().collect();
+    sorted.sort_by_key(|diagnostic| {
+        let position = diagnostic
+            .labels
+            .iter()
+            .filter(|label| label.style == LabelStyle::Primary)
+            .map(|label| (label.file_id, label.range.start))
+            .min();
+        (position.is_none(), position)
+    });
+    sorted
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// Workflow command data may not contain raw newlines, and property values
+// additionally may not contain the `:` and `,` delimiters.
+fn github_escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn github_escape_property(s: &str) -> String {
+    github_escape_data(s)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
 /// Type system for a simple functional language
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
@@ -165,6 +363,7 @@ pub struct Token {
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenKind {
     Number(i64),
+    Str(String),
     Identifier(String),
     Let,
     If,
@@ -231,6 +430,7 @@ impl<'a> Lexer<'a> {
 
         match ch {
             '0'..='9' => self.read_number(),
+            '"' => self.read_string(),
             'a'..='z' | 'A'..='Z' | '_' => self.read_identifier(),
             '+' => {
                 self.advance();
@@ -313,6 +513,24 @@ impl<'a> Lexer<'a> {
         Ok(TokenKind::Number(num))
     }
 
+    fn read_string(&mut self) -> Result<TokenKind, CompilerError> {
+        let start = self.position;
+        self.advance();
+        while self.position < self.input.len() && self.current_char() != '"' {
+            self.position += 1;
+        }
+        if self.position >= self.input.len() {
+            return Err(CompilerError::ParseError {
+                message: "unterminated string literal".to_string(),
+                location: start..self.position,
+                hint: Some("add a closing `\"`".to_string()),
+            });
+        }
+        let contents = self.input[start + 1..self.position].to_string();
+        self.advance();
+        Ok(TokenKind::Str(contents))
+    }
+
     fn read_identifier(&mut self) -> Result<TokenKind, CompilerError> {
         let start = self.position;
         while self.position < self.input.len() {
@@ -355,6 +573,18 @@ impl Project {
         file_id
     }
 
+    /// Tokenize a file and report any lexer errors as diagnostics
+    pub fn lex_file(&self, file_id: usize) -> Vec<Diagnostic<usize>> {
+        let Ok(source) = self.engine.files.source(file_id) else {
+            return Vec::new();
+        };
+
+        match Lexer::new(source).tokenize() {
+            Ok(_) => Vec::new(),
+            Err(error) => vec![error.to_diagnostic(file_id)],
+        }
+    }
+
     pub fn compile(&self) -> Result<(), Vec<Diagnostic<usize>>> {
         let mut diagnostics = Vec::new();
 
@@ -400,6 +630,154 @@ impl Default for Project {
     }
 }
 
+/// Check `let` bindings and arithmetic for mixed `int`/`string` operands
+///
+/// Statements are separated by `;` and are either `let NAME = EXPR` or a bare
+/// `EXPR`, where an expression is operands joined by arithmetic operators.
+/// Every operand of an arithmetic expression must be an `int`.
+pub fn check_types(file_id: usize, tokens: &[Token]) -> Vec<Diagnostic<usize>> {
+    let mut env: HashMap<String, Type> = HashMap::new();
+    let mut diagnostics = Vec::new();
+
+    for statement in
+        tokens.split(|token| matches!(token.kind, TokenKind::Semicolon | TokenKind::Eof))
+    {
+        match statement {
+            [keyword, Token {
+                kind: TokenKind::Identifier(name),
+                ..
+            }, equals, expr @ ..]
+                if keyword.kind == TokenKind::Let && equals.kind == TokenKind::Equals =>
+            {
+                let ty = check_expression(file_id, expr, &env, &mut diagnostics);
+                env.insert(name.clone(), ty);
+            }
+            expr => {
+                check_expression(file_id, expr, &env, &mut diagnostics);
+            }
+        }
+    }
+
+    diagnostics
+}
+
+fn check_expression(
+    file_id: usize,
+    expr: &[Token],
+    env: &HashMap<String, Type>,
+    diagnostics: &mut Vec<Diagnostic<usize>>,
+) -> Type {
+    let operands: Vec<(Type, Range<usize>)> = expr
+        .iter()
+        .filter_map(|token| {
+            let ty = match &token.kind {
+                TokenKind::Number(_) => Type::Int,
+                TokenKind::Str(_) => Type::String,
+                TokenKind::Identifier(name) => env.get(name).cloned().unwrap_or(Type::Unknown),
+                _ => return None,
+            };
+            Some((ty, token.span.clone()))
+        })
+        .collect();
+
+    let is_arithmetic = expr.iter().any(|token| {
+        matches!(
+            token.kind,
+            TokenKind::Plus | TokenKind::Minus | TokenKind::Star | TokenKind::Slash
+        )
+    });
+
+    if !is_arithmetic {
+        return operands
+            .into_iter()
+            .next()
+            .map_or(Type::Unknown, |(ty, _)| ty);
+    }
+
+    for (ty, span) in operands {
+        if ty != Type::Int && ty != Type::Unknown {
+            diagnostics.push(
+                CompilerError::TypeMismatch {
+                    expected: Type::Int,
+                    found: ty,
+                    location: span,
+                }
+                .to_diagnostic(file_id),
+            );
+        }
+    }
+
+    Type::Int
+}
+
+/// Warn about `let` bindings whose names never appear later in the file
+///
+/// Any later occurrence of the name counts as a use, including one inside or
+/// after a shadowing binding, so shadowed variables are never flagged.
+pub fn check_unused(file_id: usize, tokens: &[Token]) -> Vec<Diagnostic<usize>> {
+    let mut diagnostics = Vec::new();
+
+    for (index, window) in tokens.windows(2).enumerate() {
+        let (TokenKind::Let, TokenKind::Identifier(name)) = (&window[0].kind, &window[1].kind)
+        else {
+            continue;
+        };
+        if name.starts_with('_') {
+            continue;
+        }
+
+        let used = tokens[index + 2..]
+            .iter()
+            .any(|token| matches!(&token.kind, TokenKind::Identifier(other) if other == name));
+
+        if !used {
+            diagnostics.push(create_warning(
+                file_id,
+                &format!("unused variable `{}`", name),
+                window[1].span.clone(),
+                Some(format!(
+                    "if this is intentional, prefix it with an underscore: `_{}`",
+                    name
+                )),
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+/// Report names bound more than once by `let` or `fn` in a single file
+///
+/// Each redefinition points back at the first binding of that name.
+pub fn detect_duplicates(tokens: &[Token], file_id: usize) -> Vec<Diagnostic<usize>> {
+    let mut first_seen: HashMap<&str, Range<usize>> = HashMap::new();
+    let mut diagnostics = Vec::new();
+
+    for window in tokens.windows(2) {
+        let (TokenKind::Let | TokenKind::Function, TokenKind::Identifier(name)) =
+            (&window[0].kind, &window[1].kind)
+        else {
+            continue;
+        };
+
+        match first_seen.get(name.as_str()) {
+            Some(first_location) => diagnostics.push(
+                CompilerError::DuplicateDefinition {
+                    name: name.clone(),
+                    first_location: first_location.clone(),
+                    second_location: window[1].span.clone(),
+                }
+                .to_diagnostic(file_id),
+            ),
+            None => {
+                first_seen.insert(name, window[1].span.clone());
+            }
+        }
+    }
+
+    diagnostics
+}
+
 /// Create a warning diagnostic
 pub fn create_warning(
     file_id: usize,
@@ -427,8 +805,6 @@ pub fn create_info(file_id: usize, message: &str, location: Range<usize>) -> Dia
 
 #[cfg(test)]
 mod tests {
-    use codespan_reporting::diagnostic::Severity;
-
     use super::*;
 
     #[test]
@@ -457,4 +833,194 @@ mod tests {
         let diagnostic = error.to_diagnostic(0);
         assert_eq!(diagnostic.severity, Severity::Error);
     }
+
+    #[test]
+    fn test_render_type_mismatch() {
+        let mut engine = DiagnosticEngine::new();
+        let file_id = engine.add_file(
+            "types.ml".to_string(),
+            "let x = 1\nlet y = x + \"two\"\n".to_string(),
+        );
+        let diagnostic = CompilerError::TypeMismatch {
+            expected: Type::Int,
+            found: Type::String,
+            location: 22..27,
+        }
+        .to_diagnostic(file_id);
+
+        let output = engine.render(&diagnostic);
+        assert!(output.contains("error: type mismatch"));
+        assert!(output.contains("types.ml:2:13"));
+        assert!(output.contains("2 │ let y = x + \"two\""));
+        assert!(output.contains("^^^^^ expected `int`, found `string`"));
+    }
+
+    #[test]
+    fn test_render_sorted() {
+        let mut engine = DiagnosticEngine::new();
+        let first = engine.add_file("a.ml".to_string(), "let x = 1\nlet y = 2\n".to_string());
+        let second = engine.add_file("b.ml".to_string(), "let z = 3\n".to_string());
+
+        let diagnostics = vec![
+            Diagnostic::error()
+                .with_message("late in a.ml")
+                .with_labels(vec![Label::primary(first, 14..15)]),
+            Diagnostic::error()
+                .with_message("in b.ml")
+                .with_labels(vec![Label::primary(second, 4..5)]),
+            Diagnostic::error()
+                .with_message("early in a.ml")
+                .with_labels(vec![
+                    Label::secondary(first, 14..15),
+                    Label::primary(first, 4..5),
+                ]),
+        ];
+
+        let output = engine.render_sorted(&diagnostics);
+        let early = output.find("early in a.ml").unwrap();
+        let late = output.find("late in a.ml").unwrap();
+        let other = output.find("in b.ml").unwrap();
+        assert!(early < late);
+        assert!(late < other);
+    }
+
+    #[test]
+    fn test_min_severity() {
+        let mut engine = DiagnosticEngine::new().with_min_severity(Severity::Error);
+        let file_id = engine.add_file("quiet.ml".to_string(), "let _x = 1\n".to_string());
+
+        let warning = Diagnostic::warning()
+            .with_message("suspicious binding")
+            .with_labels(vec![Label::primary(file_id, 4..6)]);
+        let error = Diagnostic::error()
+            .with_message("broken binding")
+            .with_labels(vec![Label::primary(file_id, 9..10)]);
+
+        let output = engine.render_sorted(&[warning, error]);
+        assert!(output.contains("error: broken binding"));
+        assert!(!output.contains("suspicious binding"));
+        assert_eq!(engine.error_count(), 1);
+        assert_eq!(engine.warning_count(), 0);
+    }
+
+    #[test]
+    fn test_lex_file_reports_stray_character() {
+        let mut project = Project::new();
+        let file_id = project.add_file("stray.ml".to_string(), "let x = 1 @ 2".to_string());
+
+        let diagnostics = project.lex_file(file_id);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].labels[0].range, 10..11);
+
+        let output = project.engine.render(&diagnostics[0]);
+        assert!(output.contains("stray.ml:1:11"));
+        assert!(output.contains("^ unexpected character `@`"));
+
+        let clean = project.add_file("clean.ml".to_string(), "let y = 2".to_string());
+        assert!(project.lex_file(clean).is_empty());
+    }
+
+    #[test]
+    fn test_check_types_string_plus_int() {
+        let source = "let x = \"a\"; x + 1;";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+
+        let diagnostics = check_types(0, &tokens);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].labels[0].range, 13..14);
+        assert_eq!(
+            diagnostics[0].labels[0].message,
+            "expected `int`, found `string`"
+        );
+
+        let tokens = Lexer::new("let y = 2; let z = y + 1; z * 3;")
+            .tokenize()
+            .unwrap();
+        assert!(check_types(0, &tokens).is_empty());
+    }
+
+    #[test]
+    fn test_check_unused() {
+        let tokens = Lexer::new("let a = 1; let b = 2; b;").tokenize().unwrap();
+        let diagnostics = check_unused(0, &tokens);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].message, "unused variable `a`");
+        assert_eq!(diagnostics[0].labels[0].range, 4..5);
+
+        let tokens = Lexer::new("let a = 1; let a = a + 1; a;")
+            .tokenize()
+            .unwrap();
+        assert!(check_unused(0, &tokens).is_empty());
+
+        let tokens = Lexer::new("let _a = 1;").tokenize().unwrap();
+        assert!(check_unused(0, &tokens).is_empty());
+    }
+
+    #[test]
+    fn test_detect_duplicates() {
+        let tokens = Lexer::new("let x = 1; let x = 2;").tokenize().unwrap();
+        let diagnostics = detect_duplicates(&tokens, 0);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "duplicate definition of `x`");
+
+        let labels = &diagnostics[0].labels;
+        assert_eq!(labels.len(), 2);
+        assert_eq!(labels[0].style, LabelStyle::Secondary);
+        assert_eq!(labels[0].range, 4..5);
+        assert_eq!(labels[1].style, LabelStyle::Primary);
+        assert_eq!(labels[1].range, 15..16);
+
+        let tokens = Lexer::new("let x = 1; let y = 2;").tokenize().unwrap();
+        assert!(detect_duplicates(&tokens, 0).is_empty());
+    }
+
+    #[test]
+    fn test_emit_json() {
+        let mut engine = DiagnosticEngine::new();
+        let file_id = engine.add_file("main.ml".to_string(), "let x = \"a\" + 1".to_string());
+        let diagnostic = CompilerError::TypeMismatch {
+            expected: Type::Int,
+            found: Type::String,
+            location: 8..11,
+        }
+        .to_diagnostic(file_id)
+        .with_notes(vec!["strings cannot be added to \"int\"".to_string()]);
+
+        assert_eq!(
+            engine.emit_json(&diagnostic),
+            r#"{"severity":"error","message":"type mismatch","labels":[{"file":"main.ml","start":8,"end":11,"style":"primary","message":"expected `int`, found `string`"}],"notes":["strings cannot be added to \"int\""]}"#
+        );
+    }
+
+    #[test]
+    fn test_github_annotation() {
+        let mut engine = DiagnosticEngine::new();
+        let file_id = engine.add_file(
+            "src/main.ml".to_string(),
+            "let x = 1\nlet y = \"a\" + x".to_string(),
+        );
+        let diagnostic = CompilerError::TypeMismatch {
+            expected: Type::Int,
+            found: Type::String,
+            location: 18..21,
+        }
+        .to_diagnostic(file_id);
+        assert_eq!(
+            engine.to_github_annotation(&diagnostic),
+            "::error file=src/main.ml,line=2,col=9::type mismatch"
+        );
+
+        let warning = create_warning(file_id, "unused variable: x", 4..5, None);
+        assert_eq!(
+            engine.to_github_annotation(&warning),
+            "::warning file=src/main.ml,line=1,col=5::unused variable: x"
+        );
+
+        let unlabeled = Diagnostic::error().with_message("100% broken\nbadly");
+        assert_eq!(
+            engine.to_github_annotation(&unlabeled),
+            "::error::100%25 broken%0Abadly"
+        );
+    }
 }
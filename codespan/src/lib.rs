@@ -64,6 +64,19 @@ impl SourceFile {
         let end = span.end().to_usize();
         &self.contents[start..end]
     }
+
+    /// One span per line, from the start of the line to the start of the next
+    /// (so the newline is included) or to the end of the file. A file ending
+    /// in a newline gets a final empty span at the end, matching the line that
+    /// `line_index` reports for the end-of-file position.
+    pub fn line_ranges(&self) -> Vec<Span> {
+        let eof = ByteIndex::from(self.contents.len() as u32);
+        self.line_starts
+            .iter()
+            .zip(self.line_starts.iter().skip(1).chain(std::iter::once(&eof)))
+            .map(|(&start, &end)| Span::new(start, end))
+            .collect()
+    }
 }
 
 /// A location in a source file
@@ -480,6 +493,24 @@ mod tests {
         assert_eq!(loc.column, ColumnIndex::from(4));
     }
 
+    #[test]
+    fn test_line_ranges() {
+        let file = SourceFile::new("test.lang".to_string(), "ab\ncde\nf".to_string());
+        let ranges = file.line_ranges();
+        assert_eq!(
+            ranges,
+            vec![Span::new(0, 3), Span::new(3, 7), Span::new(7, 8)]
+        );
+        let lines: Vec<_> = ranges.iter().map(|&span| file.slice(span)).collect();
+        assert_eq!(lines, vec!["ab\n", "cde\n", "f"]);
+
+        let trailing = SourceFile::new("test.lang".to_string(), "ab\n".to_string());
+        assert_eq!(
+            trailing.line_ranges(),
+            vec![Span::new(0, 3), Span::new(3, 3)]
+        );
+    }
+
     #[test]
     fn test_span_manager() {
         let mut manager = SpanManager::new();
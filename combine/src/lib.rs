@@ -5,9 +5,10 @@ use std::collections::HashMap;
 
 use combine::parser::char::{char, digit, letter, spaces, string};
 use combine::parser::choice::choice;
-use combine::parser::repeat::{many, many1, sep_by};
+use combine::parser::combinator::not_followed_by;
+use combine::parser::repeat::{count_min_max, many, many1, sep_by, skip_many};
 use combine::parser::sequence::between;
-use combine::{eof, optional, parser, satisfy, Parser, Stream};
+use combine::{attempt, eof, one_of, optional, parser, satisfy, EasyParser, Parser, Stream};
 
 /// AST types for arithmetic expressions
 #[derive(Debug, Clone, PartialEq)]
@@ -78,18 +79,24 @@ parser! {
 }
 
 fn number<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: Stream<Token = char>, {
+    decimal().map(Expr::Number)
+}
+
+fn decimal<Input>() -> impl Parser<Input, Output = f64>
 where
     Input: Stream<Token = char>, {
     let integer = many1(digit());
-    let decimal = optional(char('.').with(many(digit())));
+    let fraction = optional(char('.').with(many(digit())));
 
-    (integer, decimal).map(|(int, dec): (String, Option<String>)| {
-        let num = if let Some(dec) = dec {
-            format!("{}.{}", int, dec)
+    (integer, fraction).map(|(int, frac): (String, Option<String>)| {
+        let num = if let Some(frac) = frac {
+            format!("{}.{}", int, frac)
         } else {
             int
         };
-        Expr::Number(num.parse().unwrap())
+        num.parse().unwrap()
     })
 }
 
@@ -125,6 +132,183 @@ impl Expr {
     }
 }
 
+/// Parse and evaluate each expression against a shared variable environment.
+/// A failure in one expression is reported in its slot without affecting the
+/// others.
+pub fn eval_batch(exprs: &[&str], vars: &HashMap<String, f64>) -> Vec<Result<f64, String>> {
+    exprs
+        .iter()
+        .map(|input| {
+            let (expr, _) = expression()
+                .skip(eof())
+                .easy_parse(*input)
+                .map_err(|e| e.to_string())?;
+            expr.eval(vars)
+        })
+        .collect()
+}
+
+/// Associativity of a binary operator in an operator table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    Left,
+    Right,
+}
+
+/// AST for expressions parsed against a caller-supplied operator table, where
+/// operators are only known by their character
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpExpr {
+    Number(f64),
+    Var(String),
+    Binary(char, Box<OpExpr>, Box<OpExpr>),
+}
+
+/// Parse expressions whose binary operators come from `ops`, a table of
+/// `(operator, precedence, associativity)` entries where a higher precedence
+/// binds tighter. Operands are numbers, identifiers and parenthesized
+/// subexpressions.
+pub fn expression_with_ops<'a, Input>(
+    ops: &'a [(char, u8, Assoc)],
+) -> impl Parser<Input, Output = OpExpr> + 'a
+where
+    Input: Stream<Token = char> + 'a, {
+    spaces().with(op_expr(ops))
+}
+
+parser! {
+    fn op_expr['a, Input](ops: &'a [(char, u8, Assoc)])(Input) -> OpExpr
+    where [Input: Stream<Token = char>]
+    {
+        let ops = *ops;
+        let op = satisfy(move |c: char| ops.iter().any(|&(op, _, _)| op == c));
+
+        op_operand(ops)
+            .skip(spaces())
+            .and(many((op.skip(spaces()), op_operand(ops).skip(spaces()))))
+            .map(move |(first, rest): (OpExpr, Vec<(char, OpExpr)>)| {
+                climb(first, &mut rest.into_iter().peekable(), 0, ops)
+            })
+    }
+}
+
+parser! {
+    fn op_operand['a, Input](ops: &'a [(char, u8, Assoc)])(Input) -> OpExpr
+    where [Input: Stream<Token = char>]
+    {
+        choice((
+            decimal().map(OpExpr::Number),
+            identifier().map(OpExpr::Var),
+            between(char('('), char(')'), spaces().with(op_expr(ops))),
+        ))
+    }
+}
+
+/// Precedence climbing over an already-parsed `operand (op operand)*`
+/// sequence. Operators binding at least as tightly as `min_prec` are folded
+/// into `lhs`; a right-associative operator lets its right operand absorb
+/// further operators of the same precedence. `min_prec` is wider than the
+/// table's `u8` so a left-associative operator at precedence 255 can still ask
+/// for "one tighter" than itself.
+fn climb(
+    mut lhs: OpExpr,
+    rest: &mut std::iter::Peekable<std::vec::IntoIter<(char, OpExpr)>>,
+    min_prec: u16,
+    ops: &[(char, u8, Assoc)],
+) -> OpExpr {
+    while let Some(&(op, _)) = rest.peek() {
+        // The operator parser only accepts characters from the table
+        let &(_, prec, assoc) = ops.iter().find(|&&(c, _, _)| c == op).unwrap();
+        let prec = u16::from(prec);
+        if prec < min_prec {
+            break;
+        }
+        let (_, operand) = rest.next().unwrap();
+        let next_min = match assoc {
+            Assoc::Left => prec + 1,
+            Assoc::Right => prec,
+        };
+        let rhs = climb(operand, rest, next_min, ops);
+        lhs = OpExpr::Binary(op, Box::new(lhs), Box::new(rhs));
+    }
+    lhs
+}
+
+/// AST types for boolean logic expressions
+#[derive(Debug, Clone, PartialEq)]
+pub enum BoolExpr {
+    Literal(bool),
+    Var(String),
+    Not(Box<BoolExpr>),
+    And(Box<BoolExpr>, Box<BoolExpr>),
+    Or(Box<BoolExpr>, Box<BoolExpr>),
+}
+
+/// Parse boolean expressions where `!` binds tighter than `&&`, which binds
+/// tighter than `||`
+pub fn bool_expression<Input>() -> impl Parser<Input, Output = BoolExpr>
+where
+    Input: Stream<Token = char>, {
+    spaces().with(bool_or())
+}
+
+parser! {
+    fn bool_or[Input]()(Input) -> BoolExpr
+    where [Input: Stream<Token = char>]
+    {
+        bool_and().skip(spaces()).and(many(string("||").skip(spaces()).with(bool_and().skip(spaces())))).map(
+            |(first, rest): (BoolExpr, Vec<BoolExpr>)| {
+                rest.into_iter().fold(first, |acc, val| BoolExpr::Or(Box::new(acc), Box::new(val)))
+            },
+        )
+    }
+}
+
+parser! {
+    fn bool_and[Input]()(Input) -> BoolExpr
+    where [Input: Stream<Token = char>]
+    {
+        bool_factor().skip(spaces()).and(many(string("&&").skip(spaces()).with(bool_factor().skip(spaces())))).map(
+            |(first, rest): (BoolExpr, Vec<BoolExpr>)| {
+                rest.into_iter().fold(first, |acc, val| BoolExpr::And(Box::new(acc), Box::new(val)))
+            },
+        )
+    }
+}
+
+parser! {
+    fn bool_factor[Input]()(Input) -> BoolExpr
+    where [Input: Stream<Token = char>]
+    {
+        choice((
+            identifier().map(|name| match name.as_str() {
+                "true" => BoolExpr::Literal(true),
+                "false" => BoolExpr::Literal(false),
+                _ => BoolExpr::Var(name),
+            }),
+            char('!').skip(spaces()).with(bool_factor()).map(|e| BoolExpr::Not(Box::new(e))),
+            between(char('('), char(')'), spaces().with(bool_or())),
+        ))
+    }
+}
+
+impl BoolExpr {
+    /// Evaluate with variable bindings, skipping the right operand of `&&`
+    /// and `||` when the left one already decides the result
+    pub fn eval(&self, vars: &HashMap<String, bool>) -> Result<bool, String> {
+        match self {
+            BoolExpr::Literal(b) => Ok(*b),
+            BoolExpr::Var(name) => vars
+                .get(name)
+                .copied()
+                .ok_or_else(|| format!("Undefined variable: {}", name)),
+            BoolExpr::Not(e) => Ok(!e.eval(vars)?),
+            BoolExpr::And(l, r) => Ok(l.eval(vars)? && r.eval(vars)?),
+            BoolExpr::Or(l, r) => Ok(l.eval(vars)? || r.eval(vars)?),
+        }
+    }
+}
+
 /// JSON value type
 #[derive(Debug, Clone, PartialEq)]
 pub enum JsonValue {
@@ -418,6 +602,259 @@ parser! {
     }
 }
 
+/// Block-level AST for a small Markdown subset
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    Heading(usize, String),
+    BulletList(Vec<String>),
+    Paragraph(String),
+}
+
+/// Parse `#` headings, `- ` bullet lists and paragraphs. Consecutive bullet
+/// lines form one list, and the lines of a paragraph are joined with single
+/// spaces until a blank line or another kind of block ends it.
+pub fn parse_markdown(input: &str) -> Result<Vec<Block>, String> {
+    markdown_document()
+        .easy_parse(input)
+        .map(|(blocks, _)| blocks)
+        .map_err(|e| e.to_string())
+}
+
+fn markdown_document<Input>() -> impl Parser<Input, Output = Vec<Block>>
+where
+    Input: Stream<Token = char>, {
+    skip_many(md_blank_line())
+        .with(many(md_block().skip(skip_many(md_blank_line()))))
+        .skip(spaces())
+        .skip(eof())
+}
+
+fn md_block<Input>() -> impl Parser<Input, Output = Block>
+where
+    Input: Stream<Token = char>, {
+    choice((md_heading(), md_bullet_list(), md_paragraph()))
+}
+
+fn md_heading<Input>() -> impl Parser<Input, Output = Block>
+where
+    Input: Stream<Token = char>, {
+    (md_heading_marker(), md_rest_of_line())
+        .map(|(level, text)| Block::Heading(level, text.trim().to_string()))
+}
+
+fn md_heading_marker<Input>() -> impl Parser<Input, Output = usize>
+where
+    Input: Stream<Token = char>, {
+    attempt(many1(char('#')).skip(char(' '))).map(|hashes: String| hashes.len())
+}
+
+fn md_bullet_list<Input>() -> impl Parser<Input, Output = Block>
+where
+    Input: Stream<Token = char>, {
+    many1(
+        md_bullet_marker()
+            .with(md_rest_of_line())
+            .map(|item| item.trim().to_string()),
+    )
+    .map(Block::BulletList)
+}
+
+fn md_bullet_marker<Input>() -> impl Parser<Input, Output = ()>
+where
+    Input: Stream<Token = char>, {
+    attempt(string("- ")).map(|_| ())
+}
+
+fn md_paragraph<Input>() -> impl Parser<Input, Output = Block>
+where
+    Input: Stream<Token = char>, {
+    many1(md_paragraph_line()).map(|lines: Vec<String>| Block::Paragraph(lines.join(" ")))
+}
+
+// Any non-blank line that doesn't open a heading or a list item
+fn md_paragraph_line<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>, {
+    attempt(
+        not_followed_by(md_heading_marker().map(|_| "heading"))
+            .with(not_followed_by(md_bullet_marker().map(|_| "list item")))
+            .with(skip_many(one_of(" \t".chars())))
+            .with((satisfy(|c: char| !c.is_whitespace()), md_rest_of_line())),
+    )
+    .map(|(first, rest)| format!("{}{}", first, rest).trim_end().to_string())
+}
+
+fn md_rest_of_line<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>, {
+    many(satisfy(|c: char| c != '\n')).skip(choice((char('\n').map(|_| ()), eof())))
+}
+
+fn md_blank_line<Input>() -> impl Parser<Input, Output = ()>
+where
+    Input: Stream<Token = char>, {
+    attempt(skip_many(one_of(" \t".chars())).with(char('\n'))).map(|_| ())
+}
+
+/// An INI section name with its `(key, value)` pairs in file order
+pub type IniSection = (String, Vec<(String, String)>);
+
+/// A single meaningful line of an INI file
+#[derive(Debug, Clone, PartialEq)]
+enum IniLine {
+    Section(String),
+    Pair(String, String),
+    Comment,
+}
+
+/// Parse an INI file into `(section, [(key, value)])` pairs in file order.
+/// Keys that appear before the first `[section]` header are collected under
+/// the section `""`, which is omitted when there are none. Keys, values and
+/// section names are trimmed; values run to the end of the line, so they may
+/// contain spaces. Lines starting with `;` or `#` are comments.
+pub fn parse_ini(input: &str) -> Result<Vec<IniSection>, String> {
+    let (lines, _) = ini_document()
+        .easy_parse(input)
+        .map_err(|e| e.to_string())?;
+
+    let mut sections = vec![(String::new(), Vec::new())];
+    for line in lines {
+        match line {
+            IniLine::Section(name) => sections.push((name, Vec::new())),
+            IniLine::Pair(key, value) => sections.last_mut().unwrap().1.push((key, value)),
+            IniLine::Comment => {}
+        }
+    }
+    if sections[0].1.is_empty() {
+        sections.remove(0);
+    }
+    Ok(sections)
+}
+
+// Blank lines and leading indentation are absorbed by `spaces()`, so every
+// line parser starts at a non-whitespace character.
+fn ini_document<Input>() -> impl Parser<Input, Output = Vec<IniLine>>
+where
+    Input: Stream<Token = char>, {
+    spaces().with(many(ini_line().skip(spaces()))).skip(eof())
+}
+
+fn ini_line<Input>() -> impl Parser<Input, Output = IniLine>
+where
+    Input: Stream<Token = char>, {
+    choice((ini_section(), ini_comment(), ini_pair()))
+}
+
+fn ini_section<Input>() -> impl Parser<Input, Output = IniLine>
+where
+    Input: Stream<Token = char>, {
+    between(
+        char('['),
+        char(']'),
+        many1(satisfy(|c: char| c != ']' && c != '\n')),
+    )
+    .skip(skip_many(one_of(" \t".chars())))
+    .map(|name: String| IniLine::Section(name.trim().to_string()))
+}
+
+fn ini_comment<Input>() -> impl Parser<Input, Output = IniLine>
+where
+    Input: Stream<Token = char>, {
+    one_of(";#".chars())
+        .with(ini_rest_of_line())
+        .map(|_| IniLine::Comment)
+}
+
+fn ini_pair<Input>() -> impl Parser<Input, Output = IniLine>
+where
+    Input: Stream<Token = char>, {
+    let key = many1(satisfy(|c: char| c != '=' && c != '\n'));
+
+    (key, char('='), ini_rest_of_line()).map(|(key, _, value): (String, _, String)| {
+        IniLine::Pair(key.trim().to_string(), value.trim().to_string())
+    })
+}
+
+fn ini_rest_of_line<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>, {
+    many(satisfy(|c: char| c != '\n'))
+}
+
+/// A timestamp in the ISO-8601 form `YYYY-MM-DDThh:mm:ss`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+fn fixed_digits<Input>(width: usize) -> impl Parser<Input, Output = u16>
+where
+    Input: Stream<Token = char>, {
+    count_min_max(width, width, digit()).map(|digits: String| digits.parse().unwrap())
+}
+
+fn datetime<Input>() -> impl Parser<Input, Output = [u16; 6]>
+where
+    Input: Stream<Token = char>, {
+    (
+        fixed_digits(4),
+        char('-').with(fixed_digits(2)),
+        char('-').with(fixed_digits(2)),
+        char('T').with(fixed_digits(2)),
+        char(':').with(fixed_digits(2)),
+        char(':').with(fixed_digits(2)),
+    )
+        .map(|(year, month, day, hour, minute, second)| [year, month, day, hour, minute, second])
+}
+
+fn days_in_month(year: u16, month: u16) -> u16 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parse a `YYYY-MM-DDThh:mm:ss` timestamp. The grammar only fixes the shape;
+/// field ranges are checked afterwards so an out-of-range value is reported
+/// by name rather than as an unexpected character.
+pub fn parse_datetime(input: &str) -> Result<DateTime, String> {
+    let ([year, month, day, hour, minute, second], _) = datetime()
+        .skip(eof())
+        .easy_parse(input)
+        .map_err(|e| e.to_string())?;
+
+    let check = |field: &str, value: u16, min: u16, max: u16| {
+        if (min..=max).contains(&value) {
+            Ok(())
+        } else {
+            Err(format!("{} {} out of range {}-{}", field, value, min, max))
+        }
+    };
+    check("month", month, 1, 12)?;
+    check("day", day, 1, days_in_month(year, month))?;
+    check("hour", hour, 0, 23)?;
+    check("minute", minute, 0, 59)?;
+    check("second", second, 0, 59)?;
+
+    Ok(DateTime {
+        year,
+        month: month as u8,
+        day: day as u8,
+        hour: hour as u8,
+        minute: minute as u8,
+        second: second as u8,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use combine::EasyParser;
@@ -452,6 +889,60 @@ mod tests {
         assert_eq!(expr.eval(&vars).unwrap(), 13.0);
     }
 
+    #[test]
+    fn test_eval_batch() {
+        let mut vars = HashMap::new();
+        vars.insert("x".to_string(), 4.0);
+        vars.insert("y".to_string(), 1.5);
+
+        let results = eval_batch(&["x+1", "y*2", "1/"], &vars);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], Ok(5.0));
+        assert_eq!(results[1], Ok(3.0));
+        assert!(results[2].is_err());
+    }
+
+    #[test]
+    fn test_bool_precedence() {
+        let (expr, _) = bool_expression().easy_parse("!a && b || c").unwrap();
+        assert_eq!(
+            expr,
+            BoolExpr::Or(
+                Box::new(BoolExpr::And(
+                    Box::new(BoolExpr::Not(Box::new(BoolExpr::Var("a".to_string())))),
+                    Box::new(BoolExpr::Var("b".to_string())),
+                )),
+                Box::new(BoolExpr::Var("c".to_string())),
+            )
+        );
+
+        let mut vars = HashMap::new();
+        vars.insert("a".to_string(), false);
+        vars.insert("b".to_string(), true);
+        vars.insert("c".to_string(), false);
+        assert_eq!(expr.eval(&vars), Ok(true));
+
+        let (expr, _) = bool_expression().easy_parse("!(a || b)").unwrap();
+        assert_eq!(expr.eval(&vars), Ok(false));
+    }
+
+    #[test]
+    fn test_bool_short_circuit() {
+        let vars = HashMap::new();
+
+        let (expr, _) = bool_expression().easy_parse("false && missing").unwrap();
+        assert_eq!(expr.eval(&vars), Ok(false));
+
+        let (expr, _) = bool_expression().easy_parse("true || missing").unwrap();
+        assert_eq!(expr.eval(&vars), Ok(true));
+
+        let (expr, _) = bool_expression().easy_parse("true && missing").unwrap();
+        assert_eq!(
+            expr.eval(&vars),
+            Err("Undefined variable: missing".to_string())
+        );
+    }
+
     #[test]
     fn test_json_parsing() {
         let result = json_value().easy_parse("null");
@@ -561,4 +1052,166 @@ mod tests {
         let result = s_expression().easy_parse("(unclosed");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_datetime() {
+        let dt = parse_datetime("2024-02-29T23:59:07").unwrap();
+        assert_eq!(
+            dt,
+            DateTime {
+                year: 2024,
+                month: 2,
+                day: 29,
+                hour: 23,
+                minute: 59,
+                second: 7,
+            }
+        );
+
+        assert!(parse_datetime("2024-2-29T23:59:07").is_err());
+        assert!(parse_datetime("2024-02-29 23:59:07").is_err());
+    }
+
+    #[test]
+    fn test_parse_datetime_out_of_range() {
+        assert_eq!(
+            parse_datetime("2024-13-01T00:00:00").unwrap_err(),
+            "month 13 out of range 1-12"
+        );
+        assert_eq!(
+            parse_datetime("2023-02-29T00:00:00").unwrap_err(),
+            "day 29 out of range 1-28"
+        );
+        assert_eq!(
+            parse_datetime("2024-01-01T24:00:00").unwrap_err(),
+            "hour 24 out of range 0-23"
+        );
+    }
+
+    #[test]
+    fn test_parse_markdown() {
+        let doc = "# Title\n\n- first item\n- second item\n\nSome text\nwrapped here.\n\nAnother paragraph.\n";
+        let blocks = parse_markdown(doc).unwrap();
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Heading(1, "Title".to_string()),
+                Block::BulletList(vec!["first item".to_string(), "second item".to_string()]),
+                Block::Paragraph("Some text wrapped here.".to_string()),
+                Block::Paragraph("Another paragraph.".to_string()),
+            ]
+        );
+
+        // Blocks can follow each other without a blank line in between
+        let blocks = parse_markdown("## Notes\n- a\ntext").unwrap();
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Heading(2, "Notes".to_string()),
+                Block::BulletList(vec!["a".to_string()]),
+                Block::Paragraph("text".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expression_with_ops() {
+        fn bin(op: char, l: OpExpr, r: OpExpr) -> OpExpr {
+            OpExpr::Binary(op, Box::new(l), Box::new(r))
+        }
+        let var = |name: &str| OpExpr::Var(name.to_string());
+
+        let table = [('+', 1, Assoc::Left), ('^', 3, Assoc::Right)];
+        let (expr, rest) = expression_with_ops(&table)
+            .easy_parse("a + b ^ c ^ d + e")
+            .unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            expr,
+            bin(
+                '+',
+                bin(
+                    '+',
+                    var("a"),
+                    bin('^', var("b"), bin('^', var("c"), var("d")))
+                ),
+                var("e"),
+            )
+        );
+
+        // Same input, but `+` now binds tightest and `^` is left-associative
+        let table = [('+', 5, Assoc::Left), ('^', 2, Assoc::Left)];
+        let (expr, _) = expression_with_ops(&table)
+            .easy_parse("a + b ^ c ^ d + e")
+            .unwrap();
+        assert_eq!(
+            expr,
+            bin(
+                '^',
+                bin('^', bin('+', var("a"), var("b")), var("c")),
+                bin('+', var("d"), var("e")),
+            )
+        );
+
+        let (expr, _) = expression_with_ops(&table)
+            .easy_parse("(1 ^ 2) + 3")
+            .unwrap();
+        assert_eq!(
+            expr,
+            bin(
+                '+',
+                bin('^', OpExpr::Number(1.0), OpExpr::Number(2.0)),
+                OpExpr::Number(3.0),
+            )
+        );
+
+        // The top of the precedence range must still be left-associative
+        let table = [('*', 255, Assoc::Left)];
+        let (expr, _) = expression_with_ops(&table).easy_parse("a*b*c").unwrap();
+        assert_eq!(expr, bin('*', bin('*', var("a"), var("b")), var("c")));
+    }
+
+    #[test]
+    fn test_parse_ini() {
+        let input = "\
+name = demo
+; global settings end here
+
+[server]
+host = example.com
+  greeting =  hello there, world  
+
+# the database section
+[database]
+url=postgres://localhost/app
+";
+        let pair = |k: &str, v: &str| (k.to_string(), v.to_string());
+        assert_eq!(
+            parse_ini(input),
+            Ok(vec![
+                (String::new(), vec![pair("name", "demo")]),
+                (
+                    "server".to_string(),
+                    vec![
+                        pair("host", "example.com"),
+                        pair("greeting", "hello there, world"),
+                    ]
+                ),
+                (
+                    "database".to_string(),
+                    vec![pair("url", "postgres://localhost/app")]
+                ),
+            ])
+        );
+
+        // No default section when every key is inside one
+        let sections = parse_ini("[only]\nkey = value").unwrap();
+        assert_eq!(
+            sections,
+            vec![("only".to_string(), vec![pair("key", "value")])]
+        );
+
+        assert_eq!(parse_ini(""), Ok(vec![]));
+        assert!(parse_ini("[section]\nno equals sign\n").is_err());
+    }
 }
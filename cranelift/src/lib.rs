@@ -10,13 +10,15 @@ use cranelift::codegen::Context;
 use cranelift::frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
 use cranelift::prelude::*;
 use cranelift_jit::{JITBuilder, JITModule};
-use cranelift_module::{FuncId, Linkage, Module};
+use cranelift_module::{DataDescription, FuncId, Linkage, Module};
 
 /// A simple JIT compiler using Cranelift
 pub struct JitCompiler {
     builder_context: FunctionBuilderContext,
     ctx: Context,
     module: JITModule,
+    capture_disassembly: bool,
+    disassembly: HashMap<FuncId, String>,
 }
 
 impl JitCompiler {
@@ -47,6 +49,18 @@ impl JitCompiler {
             builder_context: FunctionBuilderContext::new(),
             ctx: module.make_context(),
             module,
+            capture_disassembly: false,
+            disassembly: HashMap::new(),
+        }
+    }
+
+    /// Like `new`, but keeps a textual disassembly of every compiled function
+    /// for `disassemble`. Capturing costs time on each compile and memory for
+    /// the lifetime of the compiler, so it is off by default.
+    pub fn with_disassembly() -> Self {
+        Self {
+            capture_disassembly: true,
+            ..Self::new()
         }
     }
 
@@ -57,6 +71,19 @@ impl JitCompiler {
         returns: Vec<Type>,
         build_fn: impl FnOnce(&mut FunctionBuilder, &[Variable]),
     ) -> Result<FuncId, String> {
+        self.compile_function_sized(name, params, returns, build_fn)
+            .map(|(func_id, _)| func_id)
+    }
+
+    /// Like `compile_function`, but also returns the number of bytes of
+    /// machine code emitted for the function.
+    pub fn compile_function_sized(
+        &mut self,
+        name: &str,
+        params: Vec<Type>,
+        returns: Vec<Type>,
+        build_fn: impl FnOnce(&mut FunctionBuilder, &[Variable]),
+    ) -> Result<(FuncId, usize), String> {
         // Clear the context
         self.ctx.func = Function::with_name_signature(
             UserFuncName::user(0, 0),
@@ -98,14 +125,26 @@ impl JitCompiler {
             .declare_function(name, Linkage::Export, &self.ctx.func.signature)
             .map_err(|e| e.to_string())?;
 
+        // Ask the backend to keep a textual disassembly of the emitted code
+        self.ctx.set_disasm(self.capture_disassembly);
+
         self.module
             .define_function(func_id, &mut self.ctx)
             .map_err(|e| e.to_string())?;
 
+        // Record the emitted code size and disassembly before the context is cleared
+        let mut code_size = 0;
+        if let Some(code) = self.ctx.compiled_code() {
+            code_size = code.code_buffer().len();
+            if let Some(vcode) = &code.vcode {
+                self.disassembly.insert(func_id, vcode.clone());
+            }
+        }
+
         // Clear the context for next use
         self.module.clear_context(&mut self.ctx);
 
-        Ok(func_id)
+        Ok((func_id, code_size))
     }
 
     pub fn finalize(&mut self) {
@@ -116,6 +155,54 @@ impl JitCompiler {
         self.module.get_finalized_function(func_id)
     }
 
+    /// Returns a safe wrapper around a finalized `fn(i64, i64) -> i64`. The
+    /// closure borrows the compiler, so it can't outlive the JIT'd code.
+    ///
+    /// Panics if the function's declared signature doesn't match.
+    pub fn call_i64_i64(&self, func_id: FuncId) -> impl Fn(i64, i64) -> i64 + '_ {
+        self.check_signature(func_id, &[I64, I64], &[I64]);
+        // The declared signature was just checked, and the default call
+        // convention is the platform's C ABI
+        let f = unsafe {
+            std::mem::transmute::<*const u8, extern "C" fn(i64, i64) -> i64>(
+                self.get_function(func_id),
+            )
+        };
+        move |a, b| f(a, b)
+    }
+
+    /// Like `call_i64_i64`, for a finalized `fn(i64) -> i64`.
+    pub fn call_i64_1(&self, func_id: FuncId) -> impl Fn(i64) -> i64 + '_ {
+        self.check_signature(func_id, &[I64], &[I64]);
+        let f = unsafe {
+            std::mem::transmute::<*const u8, extern "C" fn(i64) -> i64>(self.get_function(func_id))
+        };
+        move |a| f(a)
+    }
+
+    fn check_signature(&self, func_id: FuncId, params: &[Type], returns: &[Type]) {
+        let signature = &self
+            .module
+            .declarations()
+            .get_function_decl(func_id)
+            .signature;
+        let types = |abi: &[AbiParam]| abi.iter().map(|p| p.value_type).collect::<Vec<_>>();
+        assert!(
+            types(&signature.params) == params && types(&signature.returns) == returns,
+            "function signature is {}, not ({:?}) -> {:?}",
+            signature,
+            params,
+            returns
+        );
+    }
+
+    /// Returns the disassembly captured when the function was compiled through
+    /// `compile_function`. Always `None` unless the compiler was created with
+    /// `with_disassembly`.
+    pub fn disassemble(&self, func_id: FuncId) -> Option<String> {
+        self.disassembly.get(&func_id).cloned()
+    }
+
     fn make_signature(&self, params: Vec<Type>, returns: Vec<Type>) -> Signature {
         let mut sig = self.module.make_signature();
         for param in params {
@@ -366,6 +453,72 @@ pub fn compile_with_print(jit: &mut JitCompiler) -> Result<FuncId, String> {
     Ok(func_id)
 }
 
+/// Example: A module-level i64 counter that each call increments and returns
+pub fn compile_with_global_counter(jit: &mut JitCompiler) -> Result<FuncId, String> {
+    // Declare a writable, zero-initialised 8-byte data object
+    let counter_id = jit
+        .module
+        .declare_data("counter", Linkage::Local, true, false)
+        .map_err(|e| e.to_string())?;
+
+    let mut data = DataDescription::new();
+    data.define(vec![0u8; 8].into_boxed_slice());
+    data.set_align(8);
+    jit.module
+        .define_data(counter_id, &data)
+        .map_err(|e| e.to_string())?;
+
+    // Define the function
+    let func_id = jit
+        .module
+        .declare_function(
+            "next_count",
+            Linkage::Export,
+            &jit.make_signature(vec![], vec![I64]),
+        )
+        .map_err(|e| e.to_string())?;
+
+    jit.ctx.func = Function::with_name_signature(
+        UserFuncName::user(0, 0),
+        jit.make_signature(vec![], vec![I64]),
+    );
+
+    // Build the function
+    {
+        let mut builder = FunctionBuilder::new(&mut jit.ctx.func, &mut jit.builder_context);
+
+        let entry_block = builder.create_block();
+        builder.switch_to_block(entry_block);
+        builder.seal_block(entry_block);
+
+        // Materialise the counter's address, then load, increment and store
+        let counter_gv = jit.module.declare_data_in_func(counter_id, builder.func);
+        let ptr_ty = jit.module.target_config().pointer_type();
+        let addr = builder.ins().global_value(ptr_ty, counter_gv);
+
+        let flags = MemFlagsData::new();
+        let current = builder.ins().load(I64, flags, addr, 0);
+        let next = builder.ins().iadd_imm(current, 1);
+        builder.ins().store(flags, next, addr, 0);
+
+        builder.ins().return_(&[next]);
+        builder.finalize();
+    }
+
+    // Verify the function
+    if let Err(errors) = verify_function(&jit.ctx.func, jit.module.isa()) {
+        return Err(format!("Function verification failed: {}", errors));
+    }
+
+    jit.module
+        .define_function(func_id, &mut jit.ctx)
+        .map_err(|e| e.to_string())?;
+
+    jit.module.clear_context(&mut jit.ctx);
+
+    Ok(func_id)
+}
+
 /// Example: Control flow with multiple returns
 pub fn compile_max(jit: &mut JitCompiler) -> Result<FuncId, String> {
     jit.compile_function("max", vec![I64, I64], vec![I64], |builder, params| {
@@ -391,6 +544,63 @@ pub fn compile_max(jit: &mut JitCompiler) -> Result<FuncId, String> {
     })
 }
 
+/// Example: Integer power with a guard block and a counted loop
+pub fn compile_pow(jit: &mut JitCompiler) -> Result<FuncId, String> {
+    jit.compile_function("pow", vec![I64, I64], vec![I64], |builder, params| {
+        let base = builder.use_var(params[0]);
+        let exp = builder.use_var(params[1]);
+
+        // Create blocks
+        let early_return = builder.create_block();
+        let header_block = builder.create_block();
+        let body_block = builder.create_block();
+        let exit_block = builder.create_block();
+
+        // Block parameters
+        builder.append_block_param(header_block, I64); // remaining
+        builder.append_block_param(header_block, I64); // acc
+
+        // Guard: exp <= 0 returns 1 without entering the loop
+        let one = builder.ins().iconst(I64, 1);
+        let non_positive = builder.ins().icmp_imm(IntCC::SignedLessThanOrEqual, exp, 0);
+        builder.ins().brif(
+            non_positive,
+            early_return,
+            &[],
+            header_block,
+            &[exp.into(), one.into()],
+        );
+
+        builder.switch_to_block(early_return);
+        builder.seal_block(early_return);
+        builder.ins().return_(&[one]);
+
+        // Header: loop while remaining > 0
+        builder.switch_to_block(header_block);
+        let remaining = builder.block_params(header_block)[0];
+        let acc = builder.block_params(header_block)[1];
+        let more = builder
+            .ins()
+            .icmp_imm(IntCC::SignedGreaterThan, remaining, 0);
+        builder.ins().brif(more, body_block, &[], exit_block, &[]);
+
+        // Body: acc *= base; remaining -= 1
+        builder.switch_to_block(body_block);
+        builder.seal_block(body_block);
+        let new_acc = builder.ins().imul(acc, base);
+        let new_remaining = builder.ins().iadd_imm(remaining, -1);
+        builder
+            .ins()
+            .jump(header_block, &[new_remaining.into(), new_acc.into()]);
+
+        // Exit: return acc
+        builder.switch_to_block(exit_block);
+        builder.seal_block(exit_block);
+        builder.seal_block(header_block);
+        builder.ins().return_(&[acc]);
+    })
+}
+
 /// Example: Array/memory operations
 pub fn compile_sum_array(jit: &mut JitCompiler) -> Result<FuncId, String> {
     jit.compile_function(
@@ -456,40 +666,114 @@ pub enum Expr {
     Add(Box<Expr>, Box<Expr>),
     Sub(Box<Expr>, Box<Expr>),
     Mul(Box<Expr>, Box<Expr>),
-    Var(usize),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+    /// Shift left; the amount is taken modulo 64, as with Cranelift's `ishl`
+    Shl(Box<Expr>, Box<Expr>),
+    /// A parameter, looked up by name in a `SymbolTable`
+    Var(String),
 }
 
 impl Expr {
-    pub fn compile(&self, builder: &mut FunctionBuilder, vars: &[Variable]) -> Value {
+    pub fn var(name: &str) -> Self {
+        Expr::Var(name.to_string())
+    }
+
+    // Every `Var` must be bound in `symbols`; the public entry points check
+    // this with `check_vars` before building anything.
+    fn compile(&self, builder: &mut FunctionBuilder, symbols: &SymbolTable) -> Value {
         match self {
             Expr::Const(n) => builder.ins().iconst(I64, *n),
-            Expr::Add(a, b) => {
-                let a_val = a.compile(builder, vars);
-                let b_val = b.compile(builder, vars);
-                builder.ins().iadd(a_val, b_val)
+            Expr::Var(name) => builder.use_var(symbols.get(name).unwrap()),
+            _ => {
+                let (a, b) = self.operands().unwrap();
+                let a_val = a.compile(builder, symbols);
+                let b_val = b.compile(builder, symbols);
+                self.emit_binary(builder, a_val, b_val)
             }
-            Expr::Sub(a, b) => {
-                let a_val = a.compile(builder, vars);
-                let b_val = b.compile(builder, vars);
-                builder.ins().isub(a_val, b_val)
+        }
+    }
+
+    fn operands(&self) -> Option<(&Expr, &Expr)> {
+        match self {
+            Expr::Add(a, b)
+            | Expr::Sub(a, b)
+            | Expr::Mul(a, b)
+            | Expr::And(a, b)
+            | Expr::Or(a, b)
+            | Expr::Xor(a, b)
+            | Expr::Shl(a, b) => Some((a, b)),
+            Expr::Const(_) | Expr::Var(_) => None,
+        }
+    }
+
+    /// Emit the instruction for a binary node whose operands are already
+    /// compiled.
+    fn emit_binary(&self, builder: &mut FunctionBuilder, a: Value, b: Value) -> Value {
+        match self {
+            Expr::Add(..) => builder.ins().iadd(a, b),
+            Expr::Sub(..) => builder.ins().isub(a, b),
+            Expr::Mul(..) => builder.ins().imul(a, b),
+            Expr::And(..) => builder.ins().band(a, b),
+            Expr::Or(..) => builder.ins().bor(a, b),
+            Expr::Xor(..) => builder.ins().bxor(a, b),
+            Expr::Shl(..) => builder.ins().ishl(a, b),
+            Expr::Const(_) | Expr::Var(_) => {
+                unreachable!("not a binary expression")
             }
-            Expr::Mul(a, b) => {
-                let a_val = a.compile(builder, vars);
-                let b_val = b.compile(builder, vars);
-                builder.ins().imul(a_val, b_val)
+        }
+    }
+
+    /// Check that every `Var` names one of `names`.
+    fn check_vars(&self, names: &[&str]) -> Result<(), String> {
+        match self {
+            Expr::Const(_) => Ok(()),
+            Expr::Var(name) if names.contains(&name.as_str()) => Ok(()),
+            Expr::Var(name) => Err(format!("undefined variable: {}", name)),
+            _ => {
+                let (a, b) = self.operands().unwrap();
+                a.check_vars(names)?;
+                b.check_vars(names)
             }
-            Expr::Var(idx) => builder.use_var(vars[*idx]),
         }
     }
 }
 
+/// Compile an expression over two `i64` parameters named `x` and `y`.
 pub fn compile_expression(jit: &mut JitCompiler, expr: Expr) -> Result<FuncId, String> {
+    compile_with_params(jit, "eval_expr", &expr, &["x", "y"])
+}
+
+/// Compile an expression whose variables are referred to by name, one `i64`
+/// parameter per entry in `param_names`.
+pub fn compile_named_expression(
+    jit: &mut JitCompiler,
+    expr: &Expr,
+    param_names: &[&str],
+) -> Result<FuncId, String> {
+    compile_with_params(jit, "eval_named_expr", expr, param_names)
+}
+
+fn compile_with_params(
+    jit: &mut JitCompiler,
+    func_name: &str,
+    expr: &Expr,
+    param_names: &[&str],
+) -> Result<FuncId, String> {
+    expr.check_vars(param_names)?;
+
     jit.compile_function(
-        "eval_expr",
-        vec![I64, I64], // two variables
+        func_name,
+        vec![I64; param_names.len()],
         vec![I64],
         |builder, params| {
-            let result = expr.compile(builder, params);
+            let mut symbols = SymbolTable::new();
+            for (name, var) in param_names.iter().zip(params) {
+                symbols.bind(name.to_string(), *var);
+            }
+
+            let result = expr.compile(builder, &symbols);
             builder.ins().return_(&[result]);
         },
     )
@@ -516,6 +800,11 @@ impl SymbolTable {
         var
     }
 
+    /// Associate `name` with an already declared variable.
+    pub fn bind(&mut self, name: String, var: Variable) {
+        self.variables.insert(name, var);
+    }
+
     pub fn get(&self, name: &str) -> Option<Variable> {
         self.variables.get(name).copied()
     }
@@ -544,6 +833,31 @@ mod tests {
         assert_eq!(add_fn(10, -5), 5);
     }
 
+    #[test]
+    fn test_call_helpers() {
+        let mut jit = JitCompiler::new();
+        let add_id = compile_add_function(&mut jit).unwrap();
+        let factorial_id = compile_factorial(&mut jit).unwrap();
+        jit.finalize();
+
+        let add = jit.call_i64_i64(add_id);
+        assert_eq!(add(2, 3), 5);
+        assert_eq!(add(10, -5), 5);
+
+        let factorial = jit.call_i64_1(factorial_id);
+        assert_eq!(factorial(5), 120);
+    }
+
+    #[test]
+    #[should_panic(expected = "function signature")]
+    fn test_call_helper_signature_mismatch() {
+        let mut jit = JitCompiler::new();
+        let add_id = compile_add_function(&mut jit).unwrap();
+        jit.finalize();
+
+        let _ = jit.call_i64_1(add_id);
+    }
+
     #[test]
     fn test_compile_factorial() {
         let mut jit = JitCompiler::new();
@@ -572,14 +886,52 @@ mod tests {
         assert_eq!(max_fn(-5, -3), -3);
     }
 
+    #[test]
+    fn test_compile_pow() {
+        let mut jit = JitCompiler::new();
+        let func_id = compile_pow(&mut jit).unwrap();
+        jit.finalize();
+
+        let code = jit.get_function(func_id);
+        let pow_fn = unsafe { std::mem::transmute::<*const u8, fn(i64, i64) -> i64>(code) };
+
+        assert_eq!(pow_fn(2, 0), 1);
+        assert_eq!(pow_fn(3, 3), 27);
+        assert_eq!(pow_fn(2, 10), 1024);
+        assert_eq!(pow_fn(-2, 3), -8);
+        assert_eq!(pow_fn(5, -3), 1);
+    }
+
+    #[test]
+    fn test_global_counter() {
+        let mut jit = JitCompiler::new();
+        let func_id = compile_with_global_counter(&mut jit).unwrap();
+
+        // Finalizing also relocates and initialises the counter's data object
+        jit.finalize();
+
+        let code = jit.get_function(func_id);
+        let next_count = unsafe { std::mem::transmute::<*const u8, fn() -> i64>(code) };
+
+        assert_eq!(next_count(), 1);
+        assert_eq!(next_count(), 2);
+        assert_eq!(next_count(), 3);
+    }
+
     #[test]
     fn test_compile_expression() {
         let mut jit = JitCompiler::new();
 
         // (x + 3) * (y - 2)
         let expr = Expr::Mul(
-            Box::new(Expr::Add(Box::new(Expr::Var(0)), Box::new(Expr::Const(3)))),
-            Box::new(Expr::Sub(Box::new(Expr::Var(1)), Box::new(Expr::Const(2)))),
+            Box::new(Expr::Add(
+                Box::new(Expr::var("x")),
+                Box::new(Expr::Const(3)),
+            )),
+            Box::new(Expr::Sub(
+                Box::new(Expr::var("y")),
+                Box::new(Expr::Const(2)),
+            )),
         );
 
         let func_id = compile_expression(&mut jit, expr).unwrap();
@@ -592,6 +944,116 @@ mod tests {
         assert_eq!(eval_fn(2, 4), 10); // (2+3) * (4-2) = 5 * 2 = 10
     }
 
+    #[test]
+    fn test_compile_bitwise_expression() {
+        let mut jit = JitCompiler::new();
+
+        // (x & 0xFF) | (y << 8)
+        let expr = Expr::Or(
+            Box::new(Expr::And(
+                Box::new(Expr::var("x")),
+                Box::new(Expr::Const(0xFF)),
+            )),
+            Box::new(Expr::Shl(
+                Box::new(Expr::var("y")),
+                Box::new(Expr::Const(8)),
+            )),
+        );
+        let func_id = compile_expression(&mut jit, expr).unwrap();
+
+        let mut shift_jit = JitCompiler::new();
+        let shift = Expr::Xor(
+            Box::new(Expr::Shl(
+                Box::new(Expr::var("x")),
+                Box::new(Expr::var("y")),
+            )),
+            Box::new(Expr::Const(1)),
+        );
+        let shift_id = compile_expression(&mut shift_jit, shift).unwrap();
+
+        jit.finalize();
+        shift_jit.finalize();
+
+        let code = jit.get_function(func_id);
+        let eval_fn = unsafe { std::mem::transmute::<*const u8, fn(i64, i64) -> i64>(code) };
+        for (x, y) in [(0x1234, 0x56), (-1, 1), (0, -1), (0xABCD, 0)] {
+            assert_eq!(eval_fn(x, y), (x & 0xFF) | (y << 8));
+        }
+
+        // Shift amounts wrap modulo 64 instead of overflowing
+        let code = shift_jit.get_function(shift_id);
+        let shift_fn = unsafe { std::mem::transmute::<*const u8, fn(i64, i64) -> i64>(code) };
+        for (x, amount) in [(1, 3), (1, 65), (3, 64), (5, -1)] {
+            assert_eq!(shift_fn(x, amount), x.wrapping_shl(amount as u32) ^ 1);
+        }
+    }
+
+    #[test]
+    fn test_compile_function_sized() {
+        let mut jit = JitCompiler::new();
+        let (func_id, size) = jit
+            .compile_function_sized("add", vec![I64, I64], vec![I64], |builder, params| {
+                let x = builder.use_var(params[0]);
+                let y = builder.use_var(params[1]);
+                let sum = builder.ins().iadd(x, y);
+                builder.ins().return_(&[sum]);
+            })
+            .unwrap();
+        jit.finalize();
+
+        assert!(size > 0);
+
+        let code = jit.get_function(func_id);
+        let add_fn = unsafe { std::mem::transmute::<*const u8, fn(i64, i64) -> i64>(code) };
+        assert_eq!(add_fn(2, 3), 5);
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let mut jit = JitCompiler::with_disassembly();
+        let func_id = compile_add_function(&mut jit).unwrap();
+
+        let disasm = jit.disassemble(func_id).unwrap();
+        assert!(!disasm.is_empty());
+        assert!(disasm.contains("ret"));
+
+        // Capture is opt-in
+        let mut jit = JitCompiler::new();
+        let func_id = compile_add_function(&mut jit).unwrap();
+        assert!(jit.disassemble(func_id).is_none());
+    }
+
+    #[test]
+    fn test_compile_named_expression() {
+        let mut jit = JitCompiler::new();
+
+        // x * y + x
+        let expr = Expr::Add(
+            Box::new(Expr::Mul(
+                Box::new(Expr::var("x")),
+                Box::new(Expr::var("y")),
+            )),
+            Box::new(Expr::var("x")),
+        );
+
+        let func_id = compile_named_expression(&mut jit, &expr, &["x", "y"]).unwrap();
+        jit.finalize();
+
+        let code = jit.get_function(func_id);
+        let eval_fn = unsafe { std::mem::transmute::<*const u8, fn(i64, i64) -> i64>(code) };
+
+        assert_eq!(eval_fn(3, 4), 15); // 3 * 4 + 3
+        assert_eq!(eval_fn(-2, 5), -12); // -2 * 5 + -2
+
+        let undefined = Expr::var("z");
+        let err = compile_named_expression(&mut JitCompiler::new(), &undefined, &["x"]);
+        assert_eq!(err.unwrap_err(), "undefined variable: z");
+
+        // compile_expression only binds `x` and `y`
+        let err = compile_expression(&mut JitCompiler::new(), undefined);
+        assert_eq!(err.unwrap_err(), "undefined variable: z");
+    }
+
     #[test]
     fn test_quadratic() {
         let mut jit = JitCompiler::new();
@@ -86,8 +86,14 @@ fn main() {
 
     // Compile: (x + 5) * (y - 3)
     let expr = Expr::Mul(
-        Box::new(Expr::Add(Box::new(Expr::Var(0)), Box::new(Expr::Const(5)))),
-        Box::new(Expr::Sub(Box::new(Expr::Var(1)), Box::new(Expr::Const(3)))),
+        Box::new(Expr::Add(
+            Box::new(Expr::var("x")),
+            Box::new(Expr::Const(5)),
+        )),
+        Box::new(Expr::Sub(
+            Box::new(Expr::var("y")),
+            Box::new(Expr::Const(3)),
+        )),
     );
 
     println!("   Expression: (x + 5) * (y - 3)");
@@ -182,11 +188,20 @@ fn main() {
     // ((x * 2) + (y * 3)) - ((x + y) * 4)
     let complex_expr = Expr::Sub(
         Box::new(Expr::Add(
-            Box::new(Expr::Mul(Box::new(Expr::Var(0)), Box::new(Expr::Const(2)))),
-            Box::new(Expr::Mul(Box::new(Expr::Var(1)), Box::new(Expr::Const(3)))),
+            Box::new(Expr::Mul(
+                Box::new(Expr::var("x")),
+                Box::new(Expr::Const(2)),
+            )),
+            Box::new(Expr::Mul(
+                Box::new(Expr::var("y")),
+                Box::new(Expr::Const(3)),
+            )),
         )),
         Box::new(Expr::Mul(
-            Box::new(Expr::Add(Box::new(Expr::Var(0)), Box::new(Expr::Var(1)))),
+            Box::new(Expr::Add(
+                Box::new(Expr::var("x")),
+                Box::new(Expr::var("y")),
+            )),
             Box::new(Expr::Const(4)),
         )),
     );
@@ -176,7 +176,10 @@ pub fn generate_multiply_by_constant(constant: i32) -> ExecutableBuffer {
 
 /// Generates a memcpy implementation optimized for small sizes.
 ///
-/// Uses a simple byte copy loop for ARM64.
+/// Creates machine code equivalent to `fn(dst: *mut u8, src: *const u8, n: u64)
+/// -> *mut u8` using a simple byte copy loop that walks both pointers forward.
+/// A count of 0 copies nothing. Overlapping regions are not supported: when
+/// `dst` starts inside `src`, bytes are overwritten before they are read.
 pub fn generate_memcpy() -> ExecutableBuffer {
     let mut ops = dynasmrt::aarch64::Assembler::new().unwrap();
 
@@ -197,6 +200,166 @@ pub fn generate_memcpy() -> ExecutableBuffer {
     ops.finalize().unwrap()
 }
 
+/// Generates a `strlen` implementation over NUL-terminated byte strings.
+///
+/// Takes a pointer to the first byte and returns the number of bytes before
+/// the terminator, so an empty string returns 0 without entering the loop body.
+pub fn generate_strlen() -> ExecutableBuffer {
+    let mut ops = dynasmrt::aarch64::Assembler::new().unwrap();
+
+    dynasm!(ops
+        ; .arch aarch64
+        ; mov x1, x0                      // Keep the start pointer in x0
+        ; ->scan:
+        ; ldrb w2, [x1]                   // Load the current byte
+        ; cbz w2, ->done                  // Stop at the NUL terminator
+        ; add x1, x1, #1                  // Advance to the next byte
+        ; b ->scan
+        ; ->done:
+        ; sub x0, x1, x0                  // Length is end minus start
+        ; ret
+    );
+
+    ops.finalize().unwrap()
+}
+
+/// Generates an addition function that reports its result to a Rust callback.
+///
+/// Creates machine code equivalent to `fn(a: i32, b: i32) -> i32` that calls
+/// `callback(a + b)` before returning the sum. The callback address is baked
+/// into the code at generation time.
+pub fn generate_callback_add(callback: extern "C" fn(i32)) -> ExecutableBuffer {
+    let mut ops = dynasmrt::aarch64::Assembler::new().unwrap();
+
+    // Load the 64-bit function address in chunks (16 bits at a time)
+    let callback_addr = callback as usize;
+    dynasm!(ops
+        ; .arch aarch64
+        ; stp x29, x30, [sp, #-16]!       // Save frame pointer and link register
+        ; stp x19, x20, [sp, #-16]!       // Save callee-saved registers
+        ; add w19, w0, w1                 // Keep the sum in a callee-saved register
+        ; mov w0, w19                     // Pass the sum as the first argument
+        ; movz x2, (callback_addr & 0xFFFF) as u32
+        ; movk x2, ((callback_addr >> 16) & 0xFFFF) as u32, lsl 16
+        ; movk x2, ((callback_addr >> 32) & 0xFFFF) as u32, lsl 32
+        ; movk x2, ((callback_addr >> 48) & 0xFFFF) as u32, lsl 48
+        ; blr x2                          // Call the callback
+        ; mov w0, w19                     // Return the sum
+        ; ldp x19, x20, [sp], #16         // Restore callee-saved registers
+        ; ldp x29, x30, [sp], #16         // Restore frame pointer and link register
+        ; ret
+    );
+
+    ops.finalize().unwrap()
+}
+
+/// Generates a dispatcher that selects one of `targets` cases with a computed
+/// branch.
+///
+/// Creates machine code equivalent to `fn(i: u32) -> u32` returning
+/// `(i + 1) * 10` for `i` in `0..targets` and `u32::MAX` otherwise. The index
+/// selects an entry in a table of `b` instructions, each 4 bytes wide, and a
+/// single `br` jumps into it, so the case is reached in constant time.
+///
+/// # Panics
+///
+/// Panics if `targets` exceeds 4095, the largest bound `cmp` can encode as an
+/// immediate.
+pub fn generate_dispatch(targets: usize) -> ExecutableBuffer {
+    assert!(targets <= 0xFFF, "dispatch supports at most 4095 targets");
+
+    let mut ops = dynasmrt::aarch64::Assembler::new().unwrap();
+    let cases: Vec<_> = (0..targets).map(|_| ops.new_dynamic_label()).collect();
+
+    dynasm!(ops
+        ; .arch aarch64
+        ; cmp w0, targets as u32          // Unsigned bounds check on the index
+        ; b.hs ->out_of_range
+        ; adr x1, ->table                 // Entry i lives at table + 4 * i
+        ; add x1, x1, w0, uxtw 2
+        ; br x1
+        ; ->table:
+    );
+    for &case in &cases {
+        dynasm!(ops
+            ; .arch aarch64
+            ; b =>case
+        );
+    }
+
+    for (i, &case) in cases.iter().enumerate() {
+        let value = (i as u32 + 1) * 10;
+        dynasm!(ops
+            ; .arch aarch64
+            ; =>case
+            ; movz w0, value
+            ; ret
+        );
+    }
+
+    dynasm!(ops
+        ; .arch aarch64
+        ; ->out_of_range:
+        ; movn w0, 0                      // All ones, i.e. u32::MAX
+        ; ret
+    );
+
+    ops.finalize().unwrap()
+}
+
+/// Generates a branch-free absolute value function.
+///
+/// Creates machine code equivalent to `fn abs(x: i32) -> i32` that compares
+/// `x` against zero and conditionally negates it with `cneg`. Negation wraps,
+/// so `abs(i32::MIN)` returns `i32::MIN` like `i32::wrapping_abs`.
+pub fn generate_abs() -> ExecutableBuffer {
+    let mut ops = dynasmrt::aarch64::Assembler::new().unwrap();
+
+    dynasm!(ops
+        ; .arch aarch64
+        ; cmp w0, #0                      // Set flags from x - 0
+        ; cneg w0, w0, lt                 // Negate only if x < 0
+        ; ret
+    );
+
+    ops.finalize().unwrap()
+}
+
+/// Generates the fused "pop `count` values and add them" opcode of a stack
+/// VM.
+///
+/// Creates machine code equivalent to `fn(stack: *const i64, len: usize) ->
+/// i64`, where `stack[len - 1]` is the top of the stack. `x1` is turned into a
+/// stack pointer one past the top, and each pop is a pre-decrementing load
+/// through it. The pops are unrolled at generation time; if the stack holds
+/// fewer than `count` values, the remaining pops are skipped.
+pub fn generate_vm_add_pop(count: usize) -> ExecutableBuffer {
+    let mut ops = dynasmrt::aarch64::Assembler::new().unwrap();
+
+    dynasm!(ops
+        ; .arch aarch64
+        ; mov x2, #0                      // Running sum
+        ; add x1, x0, x1, lsl 3           // sp = stack + len * 8
+    );
+    for _ in 0..count {
+        dynasm!(ops
+            ; .arch aarch64
+            ; cmp x1, x0                  // Stop once sp reaches the base
+            ; b.ls ->done
+            ; ldr x3, [x1, #-8]!          // Pop: sp -= 8, then load
+            ; add x2, x2, x3
+        );
+    }
+    dynasm!(ops
+        ; .arch aarch64
+        ; ->done:
+        ; mov x0, x2
+        ; ret
+    );
+
+    ops.finalize().unwrap()
+}
+
 /// Helper function to execute generated code safely.
 ///
 /// Converts the generated bytes into an executable function pointer.
@@ -273,6 +436,43 @@ mod tests {
             assert_eq!(code.len() % 4, 0);
         }
 
+        #[test]
+        fn test_strlen_generation() {
+            let code = generate_strlen();
+            assert!(!code.is_empty());
+            assert_eq!(code.len() % 4, 0);
+        }
+
+        #[test]
+        fn test_dispatch_generation() {
+            let code = generate_dispatch(4);
+            assert_eq!(code.len() % 4, 0);
+            // 5 setup instructions, 4 table entries, 4 cases of 2 and the fallback
+            assert_eq!(code.len(), (5 + 4 + 4 * 2 + 2) * 4);
+        }
+
+        #[test]
+        fn test_abs_generation() {
+            let code = generate_abs();
+            assert_eq!(code.len(), 3 * 4);
+        }
+
+        #[test]
+        fn test_vm_add_pop_generation() {
+            let code = generate_vm_add_pop(3);
+            // 2 setup instructions, 4 per pop and 2 to return
+            assert_eq!(code.len(), (2 + 3 * 4 + 2) * 4);
+        }
+
+        #[test]
+        fn test_callback_add_generation() {
+            extern "C" fn ignore(_: i32) {}
+
+            let code = generate_callback_add(ignore);
+            assert!(!code.is_empty());
+            assert_eq!(code.len() % 4, 0);
+        }
+
         #[test]
         #[cfg(target_arch = "aarch64")]
         fn test_hello_world_generation() {
@@ -295,7 +495,9 @@ mod tests {
     #[cfg(all(test, target_arch = "aarch64"))]
     #[allow(unused_unsafe)]
     mod execution {
+        use std::ffi::CString;
         use std::mem;
+        use std::sync::atomic::{AtomicI32, Ordering};
 
         use super::*;
 
@@ -343,5 +545,100 @@ mod tests {
             let mul_fn: extern "C" fn(i32) -> i32 = unsafe { mem::transmute(code.as_ptr()) };
             assert_eq!(unsafe { mul_fn(6) }, 42);
         }
+
+        #[test]
+        fn test_memcpy_execution() {
+            let code = generate_memcpy();
+            let memcpy_fn: extern "C" fn(*mut u8, *const u8, u64) -> *mut u8 =
+                unsafe { mem::transmute(code.as_ptr()) };
+
+            let source = *b"dynamic assembly";
+            let mut destination = [0u8; 16];
+            let returned = unsafe {
+                memcpy_fn(
+                    destination.as_mut_ptr(),
+                    source.as_ptr(),
+                    source.len() as u64,
+                )
+            };
+            assert_eq!(returned, destination.as_mut_ptr());
+            assert_eq!(destination, source);
+
+            let mut untouched = [7u8; 4];
+            unsafe { memcpy_fn(untouched.as_mut_ptr(), source.as_ptr(), 0) };
+            assert_eq!(untouched, [7; 4]);
+        }
+
+        #[test]
+        fn test_strlen_execution() {
+            let code = generate_strlen();
+            let strlen_fn: extern "C" fn(*const u8) -> u64 =
+                unsafe { mem::transmute(code.as_ptr()) };
+
+            for text in ["", "a", "hello, world"] {
+                let c_string = CString::new(text).unwrap();
+                let length = unsafe { strlen_fn(c_string.as_ptr().cast()) };
+                assert_eq!(length, text.len() as u64);
+            }
+        }
+
+        #[test]
+        fn test_callback_add_execution() {
+            static RECEIVED: AtomicI32 = AtomicI32::new(0);
+
+            extern "C" fn record(value: i32) {
+                RECEIVED.store(value, Ordering::SeqCst);
+            }
+
+            let code = generate_callback_add(record);
+            let add_fn: extern "C" fn(i32, i32) -> i32 = unsafe { mem::transmute(code.as_ptr()) };
+
+            assert_eq!(unsafe { add_fn(19, 23) }, 42);
+            assert_eq!(RECEIVED.load(Ordering::SeqCst), 42);
+        }
+
+        #[test]
+        fn test_dispatch_execution() {
+            let code = generate_dispatch(5);
+            let dispatch_fn: extern "C" fn(u32) -> u32 = unsafe { mem::transmute(code.as_ptr()) };
+
+            for i in 0..5 {
+                assert_eq!(unsafe { dispatch_fn(i) }, (i + 1) * 10);
+            }
+            assert_eq!(unsafe { dispatch_fn(5) }, u32::MAX);
+            assert_eq!(unsafe { dispatch_fn(u32::MAX) }, u32::MAX);
+
+            let empty = generate_dispatch(0);
+            let empty_fn: extern "C" fn(u32) -> u32 = unsafe { mem::transmute(empty.as_ptr()) };
+            assert_eq!(unsafe { empty_fn(0) }, u32::MAX);
+        }
+
+        #[test]
+        fn test_vm_add_pop_execution() {
+            let stack: Vec<i64> = vec![1, 2, 3, 4, 5, 6];
+
+            let code = generate_vm_add_pop(3);
+            let add_pop: extern "C" fn(*const i64, usize) -> i64 =
+                unsafe { mem::transmute(code.as_ptr()) };
+            assert_eq!(unsafe { add_pop(stack.as_ptr(), stack.len()) }, 4 + 5 + 6);
+            assert_eq!(unsafe { add_pop(stack.as_ptr(), 2) }, 1 + 2);
+
+            let code = generate_vm_add_pop(10);
+            let add_pop: extern "C" fn(*const i64, usize) -> i64 =
+                unsafe { mem::transmute(code.as_ptr()) };
+            assert_eq!(unsafe { add_pop(stack.as_ptr(), stack.len()) }, 21);
+        }
+
+        #[test]
+        fn test_abs_execution() {
+            let code = generate_abs();
+            let abs_fn: extern "C" fn(i32) -> i32 = unsafe { mem::transmute(code.as_ptr()) };
+
+            for x in [0, 1, 42, i32::MAX, -1, -42, -i32::MAX] {
+                assert_eq!(unsafe { abs_fn(x) }, x.abs());
+            }
+            // -i32::MIN doesn't fit in an i32, so the negation wraps back around
+            assert_eq!(unsafe { abs_fn(i32::MIN) }, i32::MIN);
+        }
     }
 }
@@ -1,14 +1,21 @@
 use std::error::Error;
 use std::path::Path;
 
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::builder::{Builder, BuilderError};
 use inkwell::context::Context;
-use inkwell::module::Module;
+use inkwell::debug_info::{
+    AsDIScope, DICompileUnit, DIFlags, DIFlagsConstants, DILocation, DISubprogram,
+    DWARFEmissionKind, DWARFSourceLanguage, DebugInfoBuilder,
+};
+use inkwell::intrinsics::Intrinsic;
+use inkwell::module::{FlagBehavior, Module};
 use inkwell::passes::PassBuilderOptions;
 use inkwell::targets::{
     CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine,
 };
 use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum};
-use inkwell::values::FunctionValue;
+use inkwell::values::{BasicValue, BasicValueEnum, FunctionValue, IntValue};
 use inkwell::{AddressSpace, IntPredicate, OptimizationLevel};
 
 /// Creates a basic LLVM context
@@ -211,6 +218,78 @@ pub fn create_loop_function<'ctx>(
     function
 }
 
+/// Builds a loop running `trip_count` times that threads `init_vals` through
+/// phi nodes, one per carried value
+///
+/// The builder must be positioned at the end of the block the loop is entered
+/// from. `body` is called with the induction variable (counting from zero, of
+/// `trip_count`'s type) and the current carried values, and returns their
+/// values for the next iteration; it may add blocks of its own as long as it
+/// leaves the builder in the block that falls through to the next iteration.
+/// Afterwards the builder sits in the exit block, and the returned values are
+/// the carried values after the last iteration (or `init_vals` if
+/// `trip_count` is not positive).
+pub fn build_counted_loop<'ctx>(
+    context: &'ctx Context,
+    builder: &Builder<'ctx>,
+    function: FunctionValue<'ctx>,
+    trip_count: IntValue<'ctx>,
+    init_vals: &[BasicValueEnum<'ctx>],
+    body: impl Fn(&Builder<'ctx>, IntValue<'ctx>, &[BasicValueEnum<'ctx>]) -> Vec<BasicValueEnum<'ctx>>,
+) -> Vec<BasicValueEnum<'ctx>> {
+    let preheader = builder.get_insert_block().unwrap();
+    let header = context.append_basic_block(function, "loop.header");
+    let body_block = context.append_basic_block(function, "loop.body");
+    let exit = context.append_basic_block(function, "loop.exit");
+
+    builder.build_unconditional_branch(header).unwrap();
+
+    // Header: one phi for the induction variable and one per carried value
+    builder.position_at_end(header);
+    let index_type = trip_count.get_type();
+    let index = builder.build_phi(index_type, "i").unwrap();
+    index.add_incoming(&[(&index_type.const_zero(), preheader)]);
+    let phis: Vec<_> = init_vals
+        .iter()
+        .map(|init| {
+            let phi = builder.build_phi(init.get_type(), "carried").unwrap();
+            phi.add_incoming(&[(init as &dyn BasicValue, preheader)]);
+            phi
+        })
+        .collect();
+    let i = index.as_basic_value().into_int_value();
+    let condition = builder
+        .build_int_compare(IntPredicate::SLT, i, trip_count, "loop.cond")
+        .unwrap();
+    builder
+        .build_conditional_branch(condition, body_block, exit)
+        .unwrap();
+
+    builder.position_at_end(body_block);
+    let current: Vec<_> = phis.iter().map(|phi| phi.as_basic_value()).collect();
+    let next = body(builder, i, &current);
+    assert_eq!(
+        next.len(),
+        phis.len(),
+        "loop body must return one value per carried value"
+    );
+    let next_i = builder
+        .build_int_add(i, index_type.const_int(1, false), "i.next")
+        .unwrap();
+
+    // The body may have moved the builder to a block of its own, so the back
+    // edge comes from wherever it ended up
+    let latch = builder.get_insert_block().unwrap();
+    builder.build_unconditional_branch(header).unwrap();
+    index.add_incoming(&[(&next_i, latch)]);
+    for (phi, value) in phis.iter().zip(&next) {
+        phi.add_incoming(&[(value as &dyn BasicValue, latch)]);
+    }
+
+    builder.position_at_end(exit);
+    current
+}
+
 /// Creates a function that allocates and uses local variables (stack
 /// allocation)
 pub fn create_alloca_function<'ctx>(
@@ -462,6 +541,254 @@ pub fn create_struct_function<'ctx>(
     function
 }
 
+/// Creates a function that adds two `<4 x i32>` vectors element-wise
+pub fn create_vector_add_function<'ctx>(
+    context: &'ctx Context,
+    module: &Module<'ctx>,
+) -> FunctionValue<'ctx> {
+    let vector_type = context.i32_type().vec_type(4);
+    let fn_type = vector_type.fn_type(&[vector_type.into(), vector_type.into()], false);
+    let function = module.add_function("vector_add", fn_type, None);
+
+    let builder = context.create_builder();
+    let entry = context.append_basic_block(function, "entry");
+    builder.position_at_end(entry);
+
+    let x = function.get_nth_param(0).unwrap().into_vector_value();
+    let y = function.get_nth_param(1).unwrap().into_vector_value();
+
+    // build_int_add accepts vectors of integers and adds lane by lane
+    let sum = builder.build_int_add(x, y, "sum").unwrap();
+    builder.build_return(Some(&sum)).unwrap();
+
+    function
+}
+
+/// Creates `clamp(x, lo, hi)` using `select` instead of branches, so the whole
+/// function is a single basic block
+pub fn create_clamp_function<'ctx>(
+    context: &'ctx Context,
+    module: &Module<'ctx>,
+) -> FunctionValue<'ctx> {
+    let i32_type = context.i32_type();
+    let fn_type = i32_type.fn_type(&[i32_type.into(), i32_type.into(), i32_type.into()], false);
+    let function = module.add_function("clamp", fn_type, None);
+
+    let builder = context.create_builder();
+    let entry = context.append_basic_block(function, "entry");
+    builder.position_at_end(entry);
+
+    let x = function.get_nth_param(0).unwrap().into_int_value();
+    let lo = function.get_nth_param(1).unwrap().into_int_value();
+    let hi = function.get_nth_param(2).unwrap().into_int_value();
+
+    // max(x, lo)
+    let below = builder
+        .build_int_compare(IntPredicate::SLT, x, lo, "below")
+        .unwrap();
+    let raised = builder
+        .build_select(below, lo, x, "raised")
+        .unwrap()
+        .into_int_value();
+
+    // min(raised, hi)
+    let above = builder
+        .build_int_compare(IntPredicate::SGT, raised, hi, "above")
+        .unwrap();
+    let clamped = builder.build_select(above, hi, raised, "clamped").unwrap();
+    builder.build_return(Some(&clamped)).unwrap();
+
+    function
+}
+
+/// Creates `divmod(a, b) -> {i32, i32}` returning the signed quotient and
+/// remainder packed into an anonymous struct, the usual lowering of multiple
+/// return values
+pub fn create_divmod_function<'ctx>(
+    context: &'ctx Context,
+    module: &Module<'ctx>,
+) -> FunctionValue<'ctx> {
+    let i32_type = context.i32_type();
+    let pair_type = context.struct_type(&[i32_type.into(), i32_type.into()], false);
+    let fn_type = pair_type.fn_type(&[i32_type.into(), i32_type.into()], false);
+    let function = module.add_function("divmod", fn_type, None);
+
+    let builder = context.create_builder();
+    let entry = context.append_basic_block(function, "entry");
+    builder.position_at_end(entry);
+
+    let a = function.get_nth_param(0).unwrap().into_int_value();
+    let b = function.get_nth_param(1).unwrap().into_int_value();
+
+    let quotient = builder.build_int_signed_div(a, b, "quotient").unwrap();
+    let remainder = builder.build_int_signed_rem(a, b, "remainder").unwrap();
+
+    // Fill the fields of an undef aggregate one at a time
+    let pair = builder
+        .build_insert_value(pair_type.get_undef(), quotient, 0, "pair.q")
+        .unwrap();
+    let pair = builder
+        .build_insert_value(pair, remainder, 1, "pair")
+        .unwrap()
+        .into_struct_value();
+    builder.build_return(Some(&pair)).unwrap();
+
+    function
+}
+
+/// Creates `zero_array(ptr, len)` that clears `len` bytes by calling the
+/// `llvm.memset.p0.i64` intrinsic
+pub fn create_zero_array_function<'ctx>(
+    context: &'ctx Context,
+    module: &Module<'ctx>,
+) -> FunctionValue<'ctx> {
+    let ptr_type = context.ptr_type(AddressSpace::default());
+    let i64_type = context.i64_type();
+    let fn_type = context
+        .void_type()
+        .fn_type(&[ptr_type.into(), i64_type.into()], false);
+    let function = module.add_function("zero_array", fn_type, None);
+
+    // memset is overloaded on the destination pointer and length types, which
+    // together select the `.p0.i64` variant
+    let memset = Intrinsic::find("llvm.memset")
+        .unwrap()
+        .get_declaration(module, &[ptr_type.into(), i64_type.into()])
+        .unwrap();
+
+    let builder = context.create_builder();
+    let entry = context.append_basic_block(function, "entry");
+    builder.position_at_end(entry);
+
+    let ptr = function.get_nth_param(0).unwrap().into_pointer_value();
+    let len = function.get_nth_param(1).unwrap().into_int_value();
+    let zero = context.i8_type().const_zero();
+    let is_volatile = context.bool_type().const_zero();
+
+    builder
+        .build_call(
+            memset,
+            &[ptr.into(), zero.into(), len.into(), is_volatile.into()],
+            "",
+        )
+        .unwrap();
+    builder.build_return(None).unwrap();
+
+    function
+}
+
+/// Adds a function-level enum attribute such as `noinline` or `alwaysinline`
+pub fn set_function_attribute<'ctx>(
+    context: &'ctx Context,
+    function: FunctionValue<'ctx>,
+    name: &str,
+) -> Result<(), String> {
+    let kind_id = Attribute::get_named_enum_kind_id(name);
+    if kind_id == 0 {
+        return Err(format!("Unknown enum attribute: {}", name));
+    }
+
+    let attribute = context.create_enum_attribute(kind_id, 0);
+    function.add_attribute(AttributeLoc::Function, attribute);
+    Ok(())
+}
+
+/// Checks whether a function carries the named enum attribute
+pub fn has_attribute(function: FunctionValue<'_>, name: &str) -> bool {
+    let kind_id = Attribute::get_named_enum_kind_id(name);
+    kind_id != 0
+        && function
+            .get_enum_attribute(AttributeLoc::Function, kind_id)
+            .is_some()
+}
+
+/// Creates a debug info builder and compile unit for a single source file
+pub fn create_debug_compile_unit<'ctx>(
+    module: &Module<'ctx>,
+    filename: &str,
+    directory: &str,
+) -> (DebugInfoBuilder<'ctx>, DICompileUnit<'ctx>) {
+    // Without this flag LLVM drops the debug metadata as an unknown version
+    let debug_metadata_version = module.get_context().i32_type().const_int(3, false);
+    module.add_basic_value_flag(
+        "Debug Info Version",
+        FlagBehavior::Warning,
+        debug_metadata_version,
+    );
+
+    module.create_debug_info_builder(
+        true,
+        DWARFSourceLanguage::C,
+        filename,
+        directory,
+        "compiler-crates",
+        false,
+        "",
+        0,
+        "",
+        DWARFEmissionKind::Full,
+        0,
+        false,
+        false,
+        "",
+        "",
+    )
+}
+
+/// Attaches a subprogram to a function so its instructions can carry
+/// locations
+pub fn create_debug_subprogram<'ctx>(
+    debug_builder: &DebugInfoBuilder<'ctx>,
+    compile_unit: DICompileUnit<'ctx>,
+    function: FunctionValue<'ctx>,
+    line: u32,
+) -> DISubprogram<'ctx> {
+    let file = compile_unit.get_file();
+    let subroutine_type = debug_builder.create_subroutine_type(file, None, &[], DIFlags::PUBLIC);
+    let subprogram = debug_builder.create_function(
+        compile_unit.as_debug_info_scope(),
+        function.get_name().to_str().unwrap_or_default(),
+        None,
+        file,
+        line,
+        subroutine_type,
+        false,
+        true,
+        line,
+        DIFlags::PUBLIC,
+        false,
+    );
+    function.set_subprogram(subprogram);
+    subprogram
+}
+
+/// Sets the builder's current debug location within its enclosing function
+///
+/// The builder must be positioned inside a function that has a subprogram.
+pub fn attach_debug_location<'ctx>(
+    context: &'ctx Context,
+    debug_builder: &DebugInfoBuilder<'ctx>,
+    builder: &Builder<'ctx>,
+    line: u32,
+    col: u32,
+) -> Result<DILocation<'ctx>, String> {
+    let subprogram = builder
+        .get_insert_block()
+        .and_then(|block| block.get_parent())
+        .and_then(|function| function.get_subprogram())
+        .ok_or("Builder is not inside a function with a subprogram")?;
+
+    let location = debug_builder.create_debug_location(
+        context,
+        line,
+        col,
+        subprogram.as_debug_info_scope(),
+        None,
+    );
+    builder.set_current_debug_location(location);
+    Ok(location)
+}
+
 /// Runs optimization passes on a module using the modern pass manager (LLVM 18)
 pub fn optimize_module<'ctx>(module: &Module<'ctx>) -> Result<(), String> {
     // First verify the module is valid
@@ -536,6 +863,26 @@ pub fn run_custom_passes<'ctx>(module: &Module<'ctx>, passes: &[&str]) -> Result
         .map_err(|e| e.to_string())
 }
 
+/// Counts the instructions in every function of the module
+pub fn count_instructions<'ctx>(module: &Module<'ctx>) -> usize {
+    module
+        .get_functions()
+        .flat_map(|function| function.get_basic_block_iter())
+        .map(|block| block.get_instructions().count())
+        .sum()
+}
+
+/// Runs only `mem2reg` and returns the instruction count before and after,
+/// showing how promoting stack slots to SSA values removes the
+/// alloca/load/store traffic
+pub fn count_instructions_before_after_mem2reg<'ctx>(
+    module: &Module<'ctx>,
+) -> Result<(usize, usize), String> {
+    let before = count_instructions(module);
+    run_custom_passes(module, &["mem2reg"])?;
+    Ok((before, count_instructions(module)))
+}
+
 /// Writes LLVM IR to a file
 pub fn write_ir_to_file<'ctx>(module: &Module<'ctx>, path: &Path) -> Result<(), String> {
     module
@@ -586,6 +933,48 @@ pub fn create_function_type<'ctx>(
     }
 }
 
+/// Evaluates `a + b` with LLVM's constant folder instead of a JIT: adding two
+/// constants through the builder yields a folded constant rather than an
+/// instruction. Overflow wraps, as `add` does without `nsw`.
+pub fn const_eval_add(a: i64, b: i64) -> i64 {
+    const_eval_i64(a, b, |builder, lhs, rhs| {
+        builder.build_int_add(lhs, rhs, "sum")
+    })
+}
+
+/// Like `const_eval_add`, for `a * b`
+pub fn const_eval_mul(a: i64, b: i64) -> i64 {
+    const_eval_i64(a, b, |builder, lhs, rhs| {
+        builder.build_int_mul(lhs, rhs, "product")
+    })
+}
+
+fn const_eval_i64(
+    a: i64,
+    b: i64,
+    build: impl for<'ctx> FnOnce(
+        &Builder<'ctx>,
+        IntValue<'ctx>,
+        IntValue<'ctx>,
+    ) -> Result<IntValue<'ctx>, BuilderError>,
+) -> i64 {
+    let context = Context::create();
+    let module = context.create_module("const_eval");
+    let i64_type = context.i64_type();
+
+    // The builder refuses to build anything until it is positioned, even
+    // though a folded constant is never inserted into the block
+    let function = module.add_function("fold", context.void_type().fn_type(&[], false), None);
+    let builder = context.create_builder();
+    builder.position_at_end(context.append_basic_block(function, "entry"));
+
+    let lhs = i64_type.const_int(a as u64, true);
+    let rhs = i64_type.const_int(b as u64, true);
+    // An operation on two constants always folds to a constant
+    let folded = build(&builder, lhs, rhs).unwrap();
+    folded.get_sign_extended_constant().unwrap()
+}
+
 /// Simple JIT execution example
 pub fn create_execution_engine<'ctx>(
     module: &Module<'ctx>,
@@ -675,6 +1064,66 @@ mod tests {
         assert!(verify_module(&module).is_ok());
     }
 
+    #[test]
+    fn test_build_counted_loop() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let i64_type = context.i64_type();
+        let fn_type = i64_type.fn_type(&[i64_type.into()], false);
+        let function = module.add_function("sum_plus_product", fn_type, None);
+        let builder = context.create_builder();
+        builder.position_at_end(context.append_basic_block(function, "entry"));
+
+        // sum = 1 + 2 + ... + n and product = 1 * 2 * ... * n
+        let n = function.get_nth_param(0).unwrap().into_int_value();
+        let init = [
+            i64_type.const_zero().into(),
+            i64_type.const_int(1, false).into(),
+        ];
+        let results = build_counted_loop(&context, &builder, function, n, &init, |b, i, vals| {
+            let k = b
+                .build_int_add(i, i64_type.const_int(1, false), "k")
+                .unwrap();
+            let sum = b.build_int_add(vals[0].into_int_value(), k, "sum").unwrap();
+            let product = b
+                .build_int_mul(vals[1].into_int_value(), k, "product")
+                .unwrap();
+            vec![sum.into(), product.into()]
+        });
+        let total = builder
+            .build_int_add(
+                results[0].into_int_value(),
+                results[1].into_int_value(),
+                "total",
+            )
+            .unwrap();
+        builder.build_return(Some(&total)).unwrap();
+
+        assert!(verify_module(&module).is_ok());
+        // entry, header, body, exit
+        assert_eq!(function.count_basic_blocks(), 4);
+        let ir = module.print_to_string().to_string();
+        assert_eq!(ir.matches(" = phi i64").count(), 3);
+
+        type SumPlusProductFunc = unsafe extern "C" fn(i64) -> i64;
+        let engine = create_execution_engine(&module).unwrap();
+        let f = unsafe {
+            engine
+                .get_function::<SumPlusProductFunc>("sum_plus_product")
+                .unwrap()
+        };
+        assert_eq!(unsafe { f.call(4) }, 10 + 24);
+        assert_eq!(unsafe { f.call(0) }, 1);
+    }
+
+    #[test]
+    fn test_const_eval() {
+        assert_eq!(const_eval_add(40, 2), 42);
+        assert_eq!(const_eval_add(-50, 8), -42);
+        assert_eq!(const_eval_add(i64::MAX, 1), i64::MIN);
+        assert_eq!(const_eval_mul(-6, 7), -42);
+    }
+
     #[test]
     fn test_global_variable() {
         let context = Context::create();
@@ -728,6 +1177,19 @@ mod tests {
         assert!(verify_module(&module).is_ok());
     }
 
+    #[test]
+    fn test_mem2reg_instruction_count() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        create_alloca_function(&context, &module);
+
+        let (before, after) = count_instructions_before_after_mem2reg(&module).unwrap();
+        // 3 allocas, 3 stores, 3 loads, add, ret collapse to add, ret
+        assert_eq!(before, 11);
+        assert!(after < before);
+        assert!(!module.print_to_string().to_string().contains("alloca"));
+    }
+
     #[test]
     fn test_jit_execution() {
         let context = Context::create();
@@ -738,4 +1200,221 @@ mod tests {
             Err(e) => panic!("JIT execution failed: {}", e),
         }
     }
+
+    #[test]
+    fn test_vector_add_function() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let vector_add = create_vector_add_function(&context, &module);
+
+        assert!(verify_module(&module).is_ok());
+        assert!(module
+            .print_to_string()
+            .to_string()
+            .contains("add <4 x i32>"));
+
+        // Vectors have no stable by-value ABI from Rust, so wrap the function in
+        // one that loads both operands from memory and stores the result
+        let ptr_type = context.ptr_type(AddressSpace::default());
+        let vector_type = context.i32_type().vec_type(4);
+        let fn_type = context
+            .void_type()
+            .fn_type(&[ptr_type.into(), ptr_type.into(), ptr_type.into()], false);
+        let wrapper = module.add_function("vector_add_ptr", fn_type, None);
+        let builder = context.create_builder();
+        builder.position_at_end(context.append_basic_block(wrapper, "entry"));
+
+        let a_ptr = wrapper.get_nth_param(0).unwrap().into_pointer_value();
+        let b_ptr = wrapper.get_nth_param(1).unwrap().into_pointer_value();
+        let out_ptr = wrapper.get_nth_param(2).unwrap().into_pointer_value();
+        let a = builder.build_load(vector_type, a_ptr, "a").unwrap();
+        let b = builder.build_load(vector_type, b_ptr, "b").unwrap();
+        let sum = builder
+            .build_call(vector_add, &[a.into(), b.into()], "sum")
+            .unwrap()
+            .try_as_basic_value()
+            .unwrap_basic();
+        builder.build_store(out_ptr, sum).unwrap();
+        builder.build_return(None).unwrap();
+        assert!(verify_module(&module).is_ok());
+
+        #[repr(C, align(16))]
+        struct Lanes([i32; 4]);
+
+        type VectorAddFunc = unsafe extern "C" fn(*const Lanes, *const Lanes, *mut Lanes);
+        let engine = create_execution_engine(&module).unwrap();
+        let vector_add_ptr = unsafe {
+            engine
+                .get_function::<VectorAddFunc>("vector_add_ptr")
+                .unwrap()
+        };
+
+        let a = Lanes([1, 2, 3, 4]);
+        let b = Lanes([10, 20, 30, -40]);
+        let mut out = Lanes([0; 4]);
+        unsafe { vector_add_ptr.call(&a, &b, &mut out) };
+        assert_eq!(out.0, [11, 22, 33, -36]);
+    }
+
+    #[test]
+    fn test_clamp_function() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let function = create_clamp_function(&context, &module);
+
+        assert!(verify_module(&module).is_ok());
+        assert_eq!(function.count_basic_blocks(), 1);
+        assert!(module.print_to_string().to_string().contains("select"));
+
+        type ClampFunc = unsafe extern "C" fn(i32, i32, i32) -> i32;
+        let engine = create_execution_engine(&module).unwrap();
+        let clamp = unsafe { engine.get_function::<ClampFunc>("clamp").unwrap() };
+
+        unsafe {
+            assert_eq!(clamp.call(-5, 0, 10), 0);
+            assert_eq!(clamp.call(0, 0, 10), 0);
+            assert_eq!(clamp.call(7, 0, 10), 7);
+            assert_eq!(clamp.call(10, 0, 10), 10);
+            assert_eq!(clamp.call(42, 0, 10), 10);
+        }
+    }
+
+    #[test]
+    fn test_divmod_function() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let divmod = create_divmod_function(&context, &module);
+
+        assert!(verify_module(&module).is_ok());
+        let ir = module.print_to_string().to_string();
+        assert!(ir.contains("define { i32, i32 } @divmod"));
+        assert!(ir.contains("insertvalue"));
+
+        // LLVM returns `{ i32, i32 }` in two registers, which is not how the C
+        // ABI returns a two-field struct, so read the result through a pointer
+        let ptr_type = context.ptr_type(AddressSpace::default());
+        let i32_type = context.i32_type();
+        let fn_type = context
+            .void_type()
+            .fn_type(&[i32_type.into(), i32_type.into(), ptr_type.into()], false);
+        let wrapper = module.add_function("divmod_ptr", fn_type, None);
+        let builder = context.create_builder();
+        builder.position_at_end(context.append_basic_block(wrapper, "entry"));
+
+        let a = wrapper.get_nth_param(0).unwrap();
+        let b = wrapper.get_nth_param(1).unwrap();
+        let out_ptr = wrapper.get_nth_param(2).unwrap().into_pointer_value();
+        let pair = builder
+            .build_call(divmod, &[a.into(), b.into()], "pair")
+            .unwrap()
+            .try_as_basic_value()
+            .unwrap_basic();
+        builder.build_store(out_ptr, pair).unwrap();
+        builder.build_return(None).unwrap();
+        assert!(verify_module(&module).is_ok());
+
+        #[repr(C)]
+        struct DivMod {
+            quotient: i32,
+            remainder: i32,
+        }
+
+        type DivModFunc = unsafe extern "C" fn(i32, i32, *mut DivMod);
+        let engine = create_execution_engine(&module).unwrap();
+        let divmod_ptr = unsafe { engine.get_function::<DivModFunc>("divmod_ptr").unwrap() };
+
+        for (a, b) in [(17, 5), (-17, 5), (17, -5), (4, 2), (0, 3)] {
+            let mut out = DivMod {
+                quotient: 0,
+                remainder: 0,
+            };
+            unsafe { divmod_ptr.call(a, b, &mut out) };
+            assert_eq!(
+                (out.quotient, out.remainder),
+                (a / b, a % b),
+                "{} divmod {}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn test_zero_array_function() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        create_zero_array_function(&context, &module);
+
+        assert!(verify_module(&module).is_ok());
+        let ir = module.print_to_string().to_string();
+        assert!(ir.contains("declare void @llvm.memset.p0.i64(ptr"));
+        assert!(ir.contains("call void @llvm.memset.p0.i64(ptr"));
+
+        type ZeroArrayFunc = unsafe extern "C" fn(*mut u8, u64);
+        let engine = create_execution_engine(&module).unwrap();
+        let zero_array = unsafe { engine.get_function::<ZeroArrayFunc>("zero_array").unwrap() };
+
+        let mut buffer = [0xAAu8; 8];
+        unsafe { zero_array.call(buffer.as_mut_ptr(), 6) };
+        assert_eq!(buffer, [0, 0, 0, 0, 0, 0, 0xAA, 0xAA]);
+    }
+
+    #[test]
+    fn test_function_attributes() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let function = create_add_function(&context, &module);
+
+        assert!(!has_attribute(function, "noinline"));
+        set_function_attribute(&context, function, "noinline").unwrap();
+        assert!(has_attribute(function, "noinline"));
+        assert!(!has_attribute(function, "alwaysinline"));
+        assert!(verify_module(&module).is_ok());
+
+        let constant = create_constant_function(&context, &module);
+        set_function_attribute(&context, constant, "alwaysinline").unwrap();
+        assert!(has_attribute(constant, "alwaysinline"));
+
+        assert!(set_function_attribute(&context, function, "not_an_attribute").is_err());
+    }
+
+    #[test]
+    fn test_debug_locations() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let (debug_builder, compile_unit) = create_debug_compile_unit(&module, "add.c", ".");
+
+        let i32_type = context.i32_type();
+        let fn_type = i32_type.fn_type(&[i32_type.into(), i32_type.into()], false);
+        let function = module.add_function("add", fn_type, None);
+        create_debug_subprogram(&debug_builder, compile_unit, function, 1);
+
+        let builder = context.create_builder();
+        builder.position_at_end(context.append_basic_block(function, "entry"));
+        let location = attach_debug_location(&context, &debug_builder, &builder, 2, 12).unwrap();
+        assert_eq!(location.get_line(), 2);
+
+        let x = function.get_nth_param(0).unwrap().into_int_value();
+        let y = function.get_nth_param(1).unwrap().into_int_value();
+        let sum = builder.build_int_add(x, y, "sum").unwrap();
+        builder.build_return(Some(&sum)).unwrap();
+        debug_builder.finalize();
+
+        assert!(verify_module(&module).is_ok());
+        let ir = module.print_to_string().to_string();
+        assert!(ir.contains("DICompileUnit"));
+        assert!(ir.contains("!DILocation(line: 2, column: 12"));
+    }
+
+    #[test]
+    fn test_debug_location_requires_subprogram() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let (debug_builder, _) = create_debug_compile_unit(&module, "add.c", ".");
+
+        let function = create_add_function(&context, &module);
+        let builder = context.create_builder();
+        builder.position_at_end(function.get_first_basic_block().unwrap());
+        assert!(attach_debug_location(&context, &debug_builder, &builder, 1, 1).is_err());
+    }
 }
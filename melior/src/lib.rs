@@ -1,9 +1,16 @@
-use melior::dialect::{arith, func, DialectRegistry};
-use melior::ir::attribute::{IntegerAttribute, StringAttribute, TypeAttribute};
-use melior::ir::operation::OperationLike;
-use melior::ir::r#type::{FunctionType, IntegerType};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use melior::diagnostic::DiagnosticHandlerId;
+use melior::dialect::{arith, func, memref, scf, DialectRegistry};
+use melior::ir::attribute::{
+    ArrayAttribute, FlatSymbolRefAttribute, FloatAttribute, IntegerAttribute, StringAttribute,
+    TypeAttribute,
+};
+use melior::ir::operation::{OperationBuilder, OperationLike};
+use melior::ir::r#type::{FunctionType, IntegerType, MemRefType};
 use melior::ir::*;
-use melior::pass::{gpu, transform, PassManager};
+use melior::pass::{conversion, gpu, transform, PassManager};
 use melior::utility::{register_all_dialects, register_all_llvm_translations, register_all_passes};
 use melior::{Context, Error};
 
@@ -128,11 +135,328 @@ pub fn create_constant(context: &Context, value: i64) -> Result<Module<'_>, Erro
     Ok(module)
 }
 
+/// Creates a function that sums the integers `0..n` with an `scf.for` loop,
+/// threading the running total through the loop as an iter-arg
+pub fn create_loop_sum_function(context: &Context) -> Result<Module<'_>, Error> {
+    let location = Location::unknown(context);
+    let module = Module::new(location);
+    let index_type = Type::index(context);
+
+    module.body().append_operation(func::func(
+        context,
+        StringAttribute::new(context, "loop_sum"),
+        TypeAttribute::new(FunctionType::new(context, &[index_type], &[index_type]).into()),
+        {
+            let block = Block::new(&[(index_type, location)]);
+
+            let zero = block
+                .append_operation(arith::constant(
+                    context,
+                    IntegerAttribute::new(index_type, 0).into(),
+                    location,
+                ))
+                .result(0)
+                .unwrap();
+            let one = block
+                .append_operation(arith::constant(
+                    context,
+                    IntegerAttribute::new(index_type, 1).into(),
+                    location,
+                ))
+                .result(0)
+                .unwrap();
+
+            // The body block receives the induction variable followed by the
+            // accumulator, and yields the updated accumulator
+            let body = Block::new(&[(index_type, location), (index_type, location)]);
+            let next = body
+                .append_operation(arith::addi(
+                    body.argument(1).unwrap().into(),
+                    body.argument(0).unwrap().into(),
+                    location,
+                ))
+                .result(0)
+                .unwrap();
+            body.append_operation(scf::r#yield(&[next.into()], location));
+
+            let loop_region = Region::new();
+            loop_region.append_block(body);
+
+            // `scf::for` has no iter-args, so the loop is built by hand with
+            // the initial accumulator as the trailing operand
+            let sum = block
+                .append_operation(
+                    OperationBuilder::new("scf.for", location)
+                        .add_operands(&[
+                            zero.into(),
+                            block.argument(0).unwrap().into(),
+                            one.into(),
+                            zero.into(),
+                        ])
+                        .add_results(&[index_type])
+                        .add_regions([loop_region])
+                        .build()?,
+                )
+                .result(0)
+                .unwrap();
+
+            block.append_operation(func::r#return(&[sum.into()], location));
+
+            let region = Region::new();
+            region.append_block(block);
+            region
+        },
+        &[],
+        location,
+    ));
+
+    Ok(module)
+}
+
+/// Creates a module with a `callee` returning a constant and a `caller` that
+/// invokes it through `func.call`
+pub fn create_caller_function(context: &Context) -> Result<Module<'_>, Error> {
+    let location = Location::unknown(context);
+    let module = Module::new(location);
+    let i64_type = IntegerType::new(context, 64).into();
+    let function_type = FunctionType::new(context, &[], &[i64_type]);
+
+    module.body().append_operation(func::func(
+        context,
+        StringAttribute::new(context, "callee"),
+        TypeAttribute::new(function_type.into()),
+        {
+            let block = Block::new(&[]);
+
+            let constant = block
+                .append_operation(arith::constant(
+                    context,
+                    IntegerAttribute::new(i64_type, 42).into(),
+                    location,
+                ))
+                .result(0)
+                .unwrap();
+
+            block.append_operation(func::r#return(&[constant.into()], location));
+
+            let region = Region::new();
+            region.append_block(block);
+            region
+        },
+        &[],
+        location,
+    ));
+
+    module.body().append_operation(func::func(
+        context,
+        StringAttribute::new(context, "caller"),
+        TypeAttribute::new(function_type.into()),
+        {
+            let block = Block::new(&[]);
+
+            // The callee is referenced by symbol name, so it resolves against
+            // the enclosing module rather than a Rust-side handle
+            let result = block
+                .append_operation(func::call(
+                    context,
+                    FlatSymbolRefAttribute::new(context, "callee"),
+                    &[],
+                    &[i64_type],
+                    location,
+                ))
+                .result(0)
+                .unwrap();
+
+            block.append_operation(func::r#return(&[result.into()], location));
+
+            let region = Region::new();
+            region.append_block(block);
+            region
+        },
+        &[],
+        location,
+    ));
+
+    Ok(module)
+}
+
+/// Creates a function that stores a constant into a `memref<1xi64>`, loads it
+/// back and returns the loaded value
+pub fn create_memref_roundtrip(context: &Context) -> Result<Module<'_>, Error> {
+    let location = Location::unknown(context);
+    let module = Module::new(location);
+    let index_type = Type::index(context);
+    let i64_type = IntegerType::new(context, 64).into();
+    let memref_type = MemRefType::new(i64_type, &[1], None, None);
+
+    module.body().append_operation(func::func(
+        context,
+        StringAttribute::new(context, "memref_roundtrip"),
+        TypeAttribute::new(FunctionType::new(context, &[], &[i64_type]).into()),
+        {
+            let block = Block::new(&[]);
+
+            let buffer = block
+                .append_operation(memref::alloc(
+                    context,
+                    memref_type,
+                    &[],
+                    &[],
+                    None,
+                    location,
+                ))
+                .result(0)
+                .unwrap();
+            let index = block
+                .append_operation(arith::constant(
+                    context,
+                    IntegerAttribute::new(index_type, 0).into(),
+                    location,
+                ))
+                .result(0)
+                .unwrap();
+            let value = block
+                .append_operation(arith::constant(
+                    context,
+                    IntegerAttribute::new(i64_type, 42).into(),
+                    location,
+                ))
+                .result(0)
+                .unwrap();
+
+            block.append_operation(memref::store(
+                value.into(),
+                buffer.into(),
+                &[index.into()],
+                location,
+            ));
+            let loaded = block
+                .append_operation(memref::load(buffer.into(), &[index.into()], location))
+                .result(0)
+                .unwrap();
+            block.append_operation(memref::dealloc(buffer.into(), location));
+
+            block.append_operation(func::r#return(&[loaded.into()], location));
+
+            let region = Region::new();
+            region.append_block(block);
+            region
+        },
+        &[],
+        location,
+    ));
+
+    Ok(module)
+}
+
+/// Creates `cmp(a: index, b: index) -> i1` returning whether `a < b`. The
+/// `index` type has no comparison op of its own in `arith`, but `arith.cmpi`
+/// accepts it alongside the fixed-width integers.
+pub fn create_index_compare(context: &Context) -> Result<Module<'_>, Error> {
+    let location = Location::unknown(context);
+    let module = Module::new(location);
+    let index_type = Type::index(context);
+    let i1_type = IntegerType::new(context, 1).into();
+
+    module.body().append_operation(func::func(
+        context,
+        StringAttribute::new(context, "cmp"),
+        TypeAttribute::new(
+            FunctionType::new(context, &[index_type, index_type], &[i1_type]).into(),
+        ),
+        {
+            let block = Block::new(&[(index_type, location), (index_type, location)]);
+
+            let less = block
+                .append_operation(arith::cmpi(
+                    context,
+                    arith::CmpiPredicate::Slt,
+                    block.argument(0).unwrap().into(),
+                    block.argument(1).unwrap().into(),
+                    location,
+                ))
+                .result(0)
+                .unwrap();
+
+            block.append_operation(func::r#return(&[less.into()], location));
+
+            let region = Region::new();
+            region.append_block(block);
+            region
+        },
+        &[],
+        location,
+    ));
+
+    Ok(module)
+}
+
 /// Shows how to verify MLIR modules
 pub fn verify_module(module: &Module<'_>) -> bool {
     module.as_operation().verify()
 }
 
+/// Like `verify_module`, but returns the verifier's diagnostics on failure.
+/// The context is needed to attach a diagnostic handler for the duration of
+/// the check.
+pub fn verify_module_detailed(context: &Context, module: &Module<'_>) -> Result<(), String> {
+    let (valid, messages) = capture_diagnostics(context, || module.as_operation().verify());
+
+    if valid {
+        Ok(())
+    } else if messages.is_empty() {
+        Err("module verification failed".to_string())
+    } else {
+        Err(messages)
+    }
+}
+
+/// Parse textual MLIR, such as the output of `module_to_string`, into a
+/// module. On failure the parser's diagnostics are returned.
+pub fn parse_module<'c>(context: &'c Context, mlir_text: &str) -> Result<Module<'c>, String> {
+    let (module, messages) = capture_diagnostics(context, || Module::parse(context, mlir_text));
+
+    module.ok_or_else(|| {
+        if messages.is_empty() {
+            "failed to parse module".to_string()
+        } else {
+            messages
+        }
+    })
+}
+
+// Runs `f` with a handler installed that collects the context's diagnostics
+// instead of printing them, one message per line.
+fn capture_diagnostics<T>(context: &Context, f: impl FnOnce() -> T) -> (T, String) {
+    // The handler owns its end of the buffer, so nothing it touches lives on
+    // this stack frame
+    let messages = Rc::new(RefCell::new(Vec::new()));
+    let sink = Rc::clone(&messages);
+    let handler = context.attach_diagnostic_handler(move |diagnostic| {
+        sink.borrow_mut().push(diagnostic.to_string());
+        true
+    });
+    let guard = DetachOnDrop { context, handler };
+    let result = f();
+    drop(guard);
+
+    let messages = messages.borrow().join("\n");
+    (result, messages)
+}
+
+// Detaches a diagnostic handler when dropped, including while unwinding out
+// of a panic in `capture_diagnostics`.
+struct DetachOnDrop<'c> {
+    context: &'c Context,
+    handler: DiagnosticHandlerId,
+}
+
+impl Drop for DetachOnDrop<'_> {
+    fn drop(&mut self) {
+        self.context.detach_diagnostic_handler(self.handler);
+    }
+}
+
 /// Shows how to print MLIR to string
 pub fn module_to_string(module: &Module<'_>) -> String {
     format!("{}", module.as_operation())
@@ -216,6 +540,21 @@ pub fn strip_debug_info(context: &Context, module: &mut Module<'_>) -> Result<()
     pass_manager.run(module)
 }
 
+/// Lower arith and func operations to the LLVM dialect
+pub fn lower_to_llvm(context: &Context, module: &mut Module<'_>) -> Result<(), Error> {
+    let pass_manager = PassManager::new(context);
+    add_llvm_lowering_passes(&pass_manager);
+    pass_manager.run(module)
+}
+
+// Shared by `lower_to_llvm` and `PassPipeline::lower_to_llvm`.
+fn add_llvm_lowering_passes(pass_manager: &PassManager<'_>) {
+    pass_manager.add_pass(conversion::create_arith_to_llvm());
+    pass_manager.add_pass(conversion::create_func_to_llvm());
+    // Drop the casts left behind where the two conversions meet
+    pass_manager.add_pass(conversion::create_reconcile_unrealized_casts());
+}
+
 /// Type builder helper for creating complex types
 pub struct TypeBuilder<'c> {
     context: &'c Context,
@@ -264,6 +603,14 @@ impl<'c> AttributeBuilder<'c> {
     pub fn type_attr(&self, ty: Type<'c>) -> TypeAttribute<'c> {
         TypeAttribute::new(ty)
     }
+
+    pub fn float(&self, ty: Type<'c>, value: f64) -> FloatAttribute<'c> {
+        FloatAttribute::new(self.context, ty, value)
+    }
+
+    pub fn array(&self, elements: &[Attribute<'c>]) -> ArrayAttribute<'c> {
+        ArrayAttribute::new(self.context, elements)
+    }
 }
 
 /// Custom pass builder for creating transformation pipelines
@@ -310,6 +657,12 @@ impl<'c> PassPipeline<'c> {
         self
     }
 
+    /// Add the arith and func conversions to the LLVM dialect
+    pub fn lower_to_llvm(self) -> Self {
+        add_llvm_lowering_passes(&self.pass_manager);
+        self
+    }
+
     /// Run the pipeline on a module
     pub fn run(self, module: &mut Module<'c>) -> Result<(), Error> {
         self.pass_manager.run(module)
@@ -350,6 +703,94 @@ mod tests {
         assert!(ir.contains("42"));
     }
 
+    #[test]
+    fn test_loop_sum_function() {
+        let context = create_test_context();
+        let module = create_loop_sum_function(&context).unwrap();
+        assert!(verify_module(&module));
+        let ir = module_to_string(&module);
+        assert!(ir.contains("func.func @loop_sum"));
+        assert!(ir.contains("scf.for"));
+        assert!(ir.contains("scf.yield"));
+    }
+
+    #[test]
+    fn test_caller_function() {
+        let context = create_test_context();
+        let module = create_caller_function(&context).unwrap();
+        assert!(verify_module(&module));
+        let ir = module_to_string(&module);
+        assert!(ir.contains("func.func @callee"));
+        assert!(ir.contains("func.func @caller"));
+        assert!(ir.contains("func.call @callee"));
+    }
+
+    #[test]
+    fn test_memref_roundtrip() {
+        let context = create_test_context();
+        let module = create_memref_roundtrip(&context).unwrap();
+        assert!(verify_module(&module));
+        let ir = module_to_string(&module);
+        assert!(ir.contains("memref.alloc"));
+        assert!(ir.contains("memref.store"));
+        assert!(ir.contains("memref.load"));
+    }
+
+    #[test]
+    fn test_index_compare() {
+        let context = create_test_context();
+        let module = create_index_compare(&context).unwrap();
+        assert!(verify_module(&module));
+        let ir = module_to_string(&module);
+        assert!(ir.contains("func.func @cmp(%arg0: index, %arg1: index) -> i1"));
+        assert!(ir.contains("arith.cmpi slt"));
+    }
+
+    #[test]
+    fn test_verify_module_detailed() {
+        let context = create_test_context();
+        let module = create_add_function(&context).unwrap();
+        assert_eq!(verify_module_detailed(&context, &module), Ok(()));
+
+        // Declared to return an index, but the body returns nothing
+        let location = Location::unknown(&context);
+        let module = Module::new(location);
+        let index_type = Type::index(&context);
+        module.body().append_operation(func::func(
+            &context,
+            StringAttribute::new(&context, "bad"),
+            TypeAttribute::new(FunctionType::new(&context, &[], &[index_type]).into()),
+            {
+                let block = Block::new(&[]);
+                block.append_operation(func::r#return(&[], location));
+
+                let region = Region::new();
+                region.append_block(block);
+                region
+            },
+            &[],
+            location,
+        ));
+
+        let error = verify_module_detailed(&context, &module).unwrap_err();
+        assert!(error.contains("func.return"), "{}", error);
+        assert!(error.contains("returns 1"), "{}", error);
+    }
+
+    #[test]
+    fn test_parse_module_roundtrip() {
+        let context = create_test_context();
+        let module = create_add_function(&context).unwrap();
+        let text = module_to_string(&module);
+
+        let parsed = parse_module(&context, &text).unwrap();
+        assert!(verify_module(&parsed));
+        assert_eq!(module_to_string(&parsed), text);
+
+        let error = parse_module(&context, "func.func @broken(").unwrap_err();
+        assert!(!error.is_empty());
+    }
+
     #[test]
     fn test_type_builder() {
         let context = create_test_context();
@@ -378,6 +819,24 @@ mod tests {
         assert_eq!(value.value(), 100);
     }
 
+    #[test]
+    fn test_float_and_array_attributes() {
+        let context = create_test_context();
+        let types = TypeBuilder::new(&context);
+        let attrs = AttributeBuilder::new(&context);
+
+        let float = attrs.float(Type::float64(&context), 2.5);
+        assert_eq!(float.value(), 2.5);
+
+        let array = attrs.array(&[
+            attrs.integer(types.i64(), 1).into(),
+            attrs.integer(types.i64(), 2).into(),
+        ]);
+        assert_eq!(array.len(), 2);
+        let second = IntegerAttribute::try_from(array.element(1).unwrap()).unwrap();
+        assert_eq!(second.value(), 2);
+    }
+
     #[test]
     fn test_canonicalization() {
         let context = create_test_context();
@@ -405,6 +864,20 @@ mod tests {
         assert!(ir.contains("func.func @multiply"));
     }
 
+    #[test]
+    fn test_lower_to_llvm() {
+        let context = create_test_context();
+        let mut module = create_add_function(&context).unwrap();
+
+        lower_to_llvm(&context, &mut module).unwrap();
+
+        assert!(verify_module(&module));
+        let ir = module_to_string(&module);
+        assert!(ir.contains("llvm.func @add"));
+        assert!(ir.contains("llvm.add"));
+        assert!(!ir.contains("arith.addi"));
+    }
+
     #[test]
     fn test_pass_pipeline_builder() {
         let context = create_test_context();
@@ -418,4 +891,21 @@ mod tests {
         pipeline.run(&mut module).unwrap();
         assert!(verify_module(&module));
     }
+
+    #[test]
+    fn test_pass_pipeline_lower_to_llvm() {
+        let context = create_test_context();
+        let mut module = create_multiply_function(&context).unwrap();
+
+        PassPipeline::new(&context)
+            .canonicalize()
+            .lower_to_llvm()
+            .run(&mut module)
+            .unwrap();
+
+        assert!(verify_module(&module));
+        let ir = module_to_string(&module);
+        assert!(ir.contains("llvm.func @multiply"));
+        assert!(ir.contains("llvm.mul"));
+    }
 }
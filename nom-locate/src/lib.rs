@@ -86,6 +86,7 @@ pub enum Expr {
         value: Box<Spanned<Expr>>,
         body: Box<Spanned<Expr>>,
     },
+    List(Vec<Spanned<Expr>>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -95,8 +96,20 @@ pub enum BinaryOp {
     Mul,
     Div,
     Eq,
+    Ne,
     Lt,
+    Le,
     Gt,
+    Ge,
+}
+
+impl BinaryOp {
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge
+        )
+    }
 }
 
 /// Parser error with precise location information
@@ -166,7 +179,11 @@ impl Parser {
                 map(char('-'), |_| (BinaryOp::Sub, 1)),
                 map(char('*'), |_| (BinaryOp::Mul, 2)),
                 map(char('/'), |_| (BinaryOp::Div, 2)),
+                // Two-character operators first, so `<=` isn't read as `<`
                 map(tag("=="), |_| (BinaryOp::Eq, 0)),
+                map(tag("!="), |_| (BinaryOp::Ne, 0)),
+                map(tag("<="), |_| (BinaryOp::Le, 0)),
+                map(tag(">="), |_| (BinaryOp::Ge, 0)),
                 map(char('<'), |_| (BinaryOp::Lt, 0)),
                 map(char('>'), |_| (BinaryOp::Gt, 0)),
             ))
@@ -202,6 +219,7 @@ impl Parser {
 
         alt((
             Self::parenthesized_expr,
+            Self::list_expr,
             Self::function_call,
             Self::let_expr,
             Self::number,
@@ -224,6 +242,26 @@ impl Parser {
         Ok((input, Spanned::new(expr.node, start_pos.0, end_pos.0)))
     }
 
+    /// Parse list literals; a trailing comma is rejected as in call arguments
+    fn list_expr(input: Span<'_>) -> IResult<Span<'_>, Spanned<Expr>> {
+        let start_pos = position(input)?;
+        let (input, _) = char('[')(input)?;
+        let (input, _) = multispace0(input)?;
+        let (input, elements) = separated_list0(
+            delimited(multispace0, char(','), multispace0),
+            Self::expression,
+        )
+        .parse(input)?;
+        let (input, _) = multispace0(input)?;
+        let (input, _) = char(']')(input)?;
+        let end_pos = position(input)?;
+
+        Ok((
+            input,
+            Spanned::new(Expr::List(elements), start_pos.0, end_pos.0),
+        ))
+    }
+
     /// Parse function calls
     fn function_call(input: Span<'_>) -> IResult<Span<'_>, Spanned<Expr>> {
         let start_pos = position(input)?;
@@ -354,6 +392,105 @@ impl Parser {
     }
 }
 
+/// Render a spanned expression tree with each node's `line:col-line:col` range
+pub fn dump_spanned(expr: &Spanned<Expr>, indent: usize) -> String {
+    let mut out = String::new();
+    write_spanned(&mut out, expr, indent);
+    out
+}
+
+fn write_spanned(out: &mut String, expr: &Spanned<Expr>, indent: usize) {
+    let indent_str = "  ".repeat(indent);
+    let location_info = format!(
+        " ({}:{}-{}:{})",
+        expr.span.start.line, expr.span.start.column, expr.span.end.line, expr.span.end.column
+    );
+
+    match &expr.node {
+        Expr::Number(n) => {
+            out.push_str(&format!("{}Number({}){}\n", indent_str, n, location_info));
+        }
+        Expr::Identifier(name) => {
+            out.push_str(&format!(
+                "{}Identifier({}){}\n",
+                indent_str, name, location_info
+            ));
+        }
+        Expr::Binary { left, op, right } => {
+            out.push_str(&format!(
+                "{}Binary({:?}){}\n",
+                indent_str, op, location_info
+            ));
+            write_spanned(out, left, indent + 1);
+            write_spanned(out, right, indent + 1);
+        }
+        Expr::Call { func, args } => {
+            out.push_str(&format!("{}Call{}\n", indent_str, location_info));
+            out.push_str(&format!("{}  func:\n", indent_str));
+            write_spanned(out, func, indent + 2);
+            out.push_str(&format!("{}  args:\n", indent_str));
+            for arg in args {
+                write_spanned(out, arg, indent + 2);
+            }
+        }
+        Expr::Let { name, value, body } => {
+            out.push_str(&format!("{}Let({}){}\n", indent_str, name, location_info));
+            out.push_str(&format!("{}  value:\n", indent_str));
+            write_spanned(out, value, indent + 2);
+            out.push_str(&format!("{}  body:\n", indent_str));
+            write_spanned(out, body, indent + 2);
+        }
+        Expr::List(elements) => {
+            out.push_str(&format!("{}List{}\n", indent_str, location_info));
+            for element in elements {
+                write_spanned(out, element, indent + 1);
+            }
+        }
+    }
+}
+
+/// Find comparisons with a comparison as an operand, such as `a < b < c`,
+/// which the grammar accepts as `(a < b) < c`. Each returned range covers the
+/// outer comparison so a frontend can point a warning at the whole chain.
+/// Parentheses are not kept in the AST, so an explicit `(a < b) < c` is
+/// reported as well.
+pub fn detect_comparison_chains(expr: &Spanned<Expr>) -> Vec<SourceRange> {
+    let mut chains = Vec::new();
+    collect_comparison_chains(expr, &mut chains);
+    chains
+}
+
+fn collect_comparison_chains(expr: &Spanned<Expr>, chains: &mut Vec<SourceRange>) {
+    let is_comparison =
+        |e: &Spanned<Expr>| matches!(&e.node, Expr::Binary { op, .. } if op.is_comparison());
+
+    match &expr.node {
+        Expr::Number(_) | Expr::Identifier(_) => {}
+        Expr::Binary { left, op, right } => {
+            if op.is_comparison() && (is_comparison(left) || is_comparison(right)) {
+                chains.push(expr.span.clone());
+            }
+            collect_comparison_chains(left, chains);
+            collect_comparison_chains(right, chains);
+        }
+        Expr::Call { func, args } => {
+            collect_comparison_chains(func, chains);
+            for arg in args {
+                collect_comparison_chains(arg, chains);
+            }
+        }
+        Expr::Let { value, body, .. } => {
+            collect_comparison_chains(value, chains);
+            collect_comparison_chains(body, chains);
+        }
+        Expr::List(elements) => {
+            for element in elements {
+                collect_comparison_chains(element, chains);
+            }
+        }
+    }
+}
+
 /// A lexer that preserves location information for each token
 pub struct LocatedLexer<'a> {
     input: Span<'a>,
@@ -588,6 +725,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_two_character_comparisons() {
+        for (source, expected) in [
+            ("a <= b", BinaryOp::Le),
+            ("a >= b", BinaryOp::Ge),
+            ("a != b", BinaryOp::Ne),
+        ] {
+            let result = Parser::parse_expression(source).unwrap();
+            assert_eq!(result.span.to_range(), 0..6, "span of {}", source);
+
+            let Expr::Binary { left, op, right } = result.node else {
+                panic!("Expected binary expression for {}", source);
+            };
+            assert_eq!(op, expected);
+            assert_eq!(left.node, Expr::Identifier("a".to_string()));
+            assert_eq!(left.span.to_range(), 0..1);
+            assert_eq!(right.node, Expr::Identifier("b".to_string()));
+            assert_eq!(right.span.to_range(), 5..6);
+        }
+
+        let result = Parser::parse_expression("x+1>=y").unwrap();
+        let Expr::Binary { left, op, .. } = result.node else {
+            panic!("Expected binary expression");
+        };
+        assert_eq!(op, BinaryOp::Ge);
+        assert_eq!(left.span.to_range(), 0..3);
+        assert_eq!(result.span.to_range(), 0..6);
+    }
+
     #[test]
     fn test_function_call_parsing() {
         let result = Parser::parse_expression("add(1, 2)").unwrap();
@@ -684,4 +850,74 @@ mod tests {
         let range = result.span.to_range();
         assert_eq!(range, 0..6);
     }
+
+    #[test]
+    fn test_list_literal() {
+        let result = Parser::parse_expression("[1, 2, 3]").unwrap();
+        assert_eq!(result.span.to_range(), 0..9);
+
+        let Expr::List(elements) = result.node else {
+            panic!("Expected list literal");
+        };
+        let values: Vec<_> = elements.iter().map(|e| e.node.clone()).collect();
+        assert_eq!(
+            values,
+            vec![Expr::Number(1), Expr::Number(2), Expr::Number(3)]
+        );
+        let ranges: Vec<_> = elements.iter().map(|e| e.span.to_range()).collect();
+        assert_eq!(ranges, vec![1..2, 4..5, 7..8]);
+        assert_eq!(elements[2].span.start.column, 8);
+    }
+
+    #[test]
+    fn test_empty_list() {
+        let result = Parser::parse_expression("[]").unwrap();
+        assert_eq!(result.node, Expr::List(vec![]));
+        assert_eq!(result.span.to_range(), 0..2);
+
+        let result = Parser::parse_expression("[ ]").unwrap();
+        assert_eq!(result.node, Expr::List(vec![]));
+    }
+
+    #[test]
+    fn test_list_trailing_comma_rejected() {
+        assert!(Parser::parse_expression("[1, 2,]").is_err());
+    }
+
+    #[test]
+    fn test_dump_spanned() {
+        let result = Parser::parse_expression("2 + 3 * 4").unwrap();
+        let dump = dump_spanned(&result, 0);
+
+        let expected = concat!(
+            "Binary(Add) (1:1-1:10)\n",
+            "  Number(2) (1:1-1:2)\n",
+            "  Binary(Mul) (1:5-1:10)\n",
+            "    Number(3) (1:5-1:6)\n",
+            "    Number(4) (1:9-1:10)\n",
+        );
+        assert_eq!(dump, expected);
+    }
+
+    #[test]
+    fn test_single_comparison_is_not_a_chain() {
+        let result = Parser::parse_expression("a < b").unwrap();
+        assert!(detect_comparison_chains(&result).is_empty());
+
+        let result = Parser::parse_expression("a + 1 < b * 2").unwrap();
+        assert!(detect_comparison_chains(&result).is_empty());
+    }
+
+    #[test]
+    fn test_comparison_chain_detected() {
+        let result = Parser::parse_expression("a < b < c").unwrap();
+        let chains = detect_comparison_chains(&result);
+        assert_eq!(chains.len(), 1);
+        assert_eq!(chains[0].to_range(), 0..9);
+
+        let result = Parser::parse_expression("f(x, 1 < y > 2)").unwrap();
+        let chains = detect_comparison_chains(&result);
+        assert_eq!(chains.len(), 1);
+        assert_eq!(chains[0].to_range(), 5..14);
+    }
 }
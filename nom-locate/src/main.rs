@@ -1,4 +1,4 @@
-use nom_locate_example::{Expr, LocatedLexer, Parser, Spanned, TokenKind};
+use nom_locate_example::{dump_spanned, LocatedLexer, Parser, TokenKind};
 
 fn main() {
     println!("=== nom-locate: Location-Aware Parsing ===");
@@ -33,6 +33,7 @@ fn demo_expression_parsing() {
         "add(1, 2)",
         "let x = 5 in x * 2",
         "(a + b) * c",
+        "[1, x + 2, f(3)]",
     ];
 
     for expr in expressions {
@@ -184,7 +185,7 @@ fn demo_complex_expression() {
     match Parser::parse_expression(complex_expr) {
         Ok(parsed) => {
             println!("Successfully parsed!");
-            print_expression_tree(&parsed, 0);
+            print!("{}", dump_spanned(&parsed, 0));
         }
         Err(e) => {
             println!(
@@ -194,41 +195,3 @@ fn demo_complex_expression() {
         }
     }
 }
-
-fn print_expression_tree(expr: &Spanned<Expr>, indent: usize) {
-    let indent_str = "  ".repeat(indent);
-    let location_info = format!(
-        " ({}:{}-{}:{})",
-        expr.span.start.line, expr.span.start.column, expr.span.end.line, expr.span.end.column
-    );
-
-    match &expr.node {
-        Expr::Number(n) => {
-            println!("{}Number({}){}", indent_str, n, location_info);
-        }
-        Expr::Identifier(name) => {
-            println!("{}Identifier({}){}", indent_str, name, location_info);
-        }
-        Expr::Binary { left, op, right } => {
-            println!("{}Binary({:?}){}", indent_str, op, location_info);
-            print_expression_tree(left, indent + 1);
-            print_expression_tree(right, indent + 1);
-        }
-        Expr::Call { func, args } => {
-            println!("{}Call{}", indent_str, location_info);
-            println!("{}  func:", indent_str);
-            print_expression_tree(func, indent + 2);
-            println!("{}  args:", indent_str);
-            for arg in args {
-                print_expression_tree(arg, indent + 2);
-            }
-        }
-        Expr::Let { name, value, body } => {
-            println!("{}Let({}){}", indent_str, name, location_info);
-            println!("{}  value:", indent_str);
-            print_expression_tree(value, indent + 2);
-            println!("{}  body:", indent_str);
-            print_expression_tree(body, indent + 2);
-        }
-    }
-}
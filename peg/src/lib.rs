@@ -26,7 +26,7 @@ pub enum Expr {
         body: Box<Expr>,
     },
     Let {
-        bindings: Vec<(String, Expr)>,
+        bindings: Vec<(String, Option<TypeExpr>, Expr)>,
         body: Box<Expr>,
     },
     If {
@@ -36,6 +36,10 @@ pub enum Expr {
     },
     List(Vec<Expr>),
     Record(HashMap<String, Expr>),
+    RecordUpdate {
+        base: Box<Expr>,
+        fields: Vec<(String, Expr)>,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -64,11 +68,20 @@ pub enum UnaryOp {
     Not,
 }
 
+/// Type annotations on `def` and `let` bindings
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeExpr {
+    Named(String),
+    List(Box<TypeExpr>),
+    Function(Box<TypeExpr>, Box<TypeExpr>),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Statement {
     Expression(Expr),
     Definition {
         name: String,
+        ty: Option<TypeExpr>,
         value: Expr,
     },
     TypeDef {
@@ -82,6 +95,46 @@ pub struct Program {
     pub statements: Vec<Statement>,
 }
 
+/// Arithmetic expression annotated with source positions, produced by
+/// `parse_program_spanned`. Binary nodes are positioned at their operator and
+/// all other nodes at their first character.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedExpr {
+    pub kind: SpannedExprKind,
+    pub position: peg::str::LineCol,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpannedExprKind {
+    Number(i64),
+    Float(f64),
+    Identifier(String),
+    Binary {
+        left: Box<SpannedExpr>,
+        op: BinaryOp,
+        right: Box<SpannedExpr>,
+    },
+    Unary {
+        op: UnaryOp,
+        expr: Box<SpannedExpr>,
+    },
+}
+
+/// Evaluation error located at the node that caused it
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalError {
+    pub message: String,
+    pub position: peg::str::LineCol,
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for EvalError {}
+
 /// Error type for parser errors
 #[derive(Debug, Clone)]
 pub struct ParseError {
@@ -117,10 +170,26 @@ peg::parser! {
 
         /// Parse a variable definition
         rule definition() -> Statement
-            = "def" _ name:identifier() _ "=" _ value:expression() _ {
-                Statement::Definition { name, value }
+            = "def" _ name:identifier() ty:type_annotation()? _ "=" _ value:expression() _ {
+                Statement::Definition { name, ty, value }
             }
 
+        /// Parse an optional `: Type` annotation
+        rule type_annotation() -> TypeExpr
+            = _ ":" _ ty:type_expr() { ty }
+
+        /// Parse a type; `->` is right-associative, so `A -> B -> C` is `A -> (B -> C)`
+        rule type_expr() -> TypeExpr
+            = from:type_atom() _ "->" _ to:type_expr() {
+                TypeExpr::Function(Box::new(from), Box::new(to))
+            }
+            / type_atom()
+
+        rule type_atom() -> TypeExpr
+            = "[" _ elem:type_expr() _ "]" { TypeExpr::List(Box::new(elem)) }
+            / "(" _ ty:type_expr() _ ")" { ty }
+            / name:identifier() { TypeExpr::Named(name) }
+
         /// Parse a type definition
         rule type_definition() -> Statement
             = "type" _ name:identifier() _ "=" _ constructors:constructor_list() _ {
@@ -184,6 +253,46 @@ peg::parser! {
             e:postfix() { e }
         }
 
+        /// Parse a sequence of arithmetic expressions, recording where each
+        /// node starts. `src` must be the whole input so offsets can be turned
+        /// into line/column pairs; it is crate-private so that callers go
+        /// through `parse_program_spanned`, which passes the input for both.
+        pub(crate) rule program_spanned(src: &str) -> Vec<SpannedExpr>
+            = _ exprs:(e:spanned_expression(src) _ { e })* { exprs }
+
+        /// The arithmetic subset of `expression` that `eval_spanned` understands
+        rule spanned_expression(src: &str) -> SpannedExpr = precedence!{
+            x:(@) _ p:position!() "+" _ y:@ { spanned_binary(src, p, x, BinaryOp::Add, y) }
+            x:(@) _ p:position!() "-" _ y:@ { spanned_binary(src, p, x, BinaryOp::Sub, y) }
+            --
+            x:(@) _ p:position!() "*" _ y:@ { spanned_binary(src, p, x, BinaryOp::Mul, y) }
+            x:(@) _ p:position!() "/" _ y:@ { spanned_binary(src, p, x, BinaryOp::Div, y) }
+            x:(@) _ p:position!() "%" _ y:@ { spanned_binary(src, p, x, BinaryOp::Mod, y) }
+            --
+            x:@ _ p:position!() "**" _ y:(@) { spanned_binary(src, p, x, BinaryOp::Pow, y) }
+            --
+            p:position!() "-" _ e:@ {
+                SpannedExpr {
+                    kind: SpannedExprKind::Unary { op: UnaryOp::Neg, expr: Box::new(e) },
+                    position: peg::Parse::position_repr(src, p),
+                }
+            }
+            --
+            e:spanned_atom(src) { e }
+        }
+
+        rule spanned_atom(src: &str) -> SpannedExpr
+            = p:position!() e:(float() / number() / identifier_expr()) {
+                let kind = match e {
+                    Expr::Float(f) => SpannedExprKind::Float(f),
+                    Expr::Number(n) => SpannedExprKind::Number(n),
+                    Expr::Identifier(name) => SpannedExprKind::Identifier(name),
+                    _ => unreachable!("spanned_atom only matches literals and identifiers"),
+                };
+                SpannedExpr { kind, position: peg::Parse::position_repr(src, p) }
+            }
+            / "(" _ e:spanned_expression(src) _ ")" { e }
+
         /// Postfix expressions (function calls)
         rule postfix() -> Expr
             = e:atom() calls:call_suffix()* {
@@ -202,6 +311,7 @@ peg::parser! {
             / string_literal()
             / boolean()
             / list()
+            / record_update()  // Must come before record
             / record()
             / lambda()
             / let_expression()
@@ -273,6 +383,13 @@ peg::parser! {
                 Expr::Record(fields.into_iter().collect())
             }
 
+        /// Parse functional record updates: `{ base with a: 1, b: 2 }`
+        rule record_update() -> Expr
+            = "{" _ base:expression() _ "with" !identifier_char() _
+              fields:(field() ++ (_ "," _)) _ "}" {
+                Expr::RecordUpdate { base: Box::new(base), fields }
+            }
+
         /// Parse field lists for records
         rule field_list() -> Vec<(String, Expr)>
             = head:field() tail:(_ "," _ f:field() { f })* {
@@ -316,7 +433,7 @@ peg::parser! {
             }
 
         /// Parse binding lists for let expressions
-        rule binding_list() -> Vec<(String, Expr)>
+        rule binding_list() -> Vec<(String, Option<TypeExpr>, Expr)>
             = head:binding() tail:(_ "," _ b:binding() { b })* {
                 let mut result = vec![head];
                 result.extend(tail);
@@ -324,9 +441,9 @@ peg::parser! {
             }
 
         /// Parse a single binding
-        rule binding() -> (String, Expr)
-            = name:identifier() _ "=" _ value:expression() {
-                (name, value)
+        rule binding() -> (String, Option<TypeExpr>, Expr)
+            = name:identifier() ty:type_annotation()? _ "=" _ value:expression() {
+                (name, ty, value)
             }
 
         /// Parse if expressions
@@ -357,7 +474,7 @@ peg::parser! {
         /// Reserved words that can't be identifiers
         rule reserved_word()
             = ("if" / "then" / "else" / "let" / "in" / "fn" / "def" / "type"
-               / "true" / "false" / "not") !identifier_char()
+               / "true" / "false" / "not" / "with") !identifier_char()
 
         /// Whitespace
         rule _() = quiet!{ (whitespace() / comment())* }
@@ -367,7 +484,11 @@ peg::parser! {
 
         rule comment()
             = "//" (!"\n" [_])*
-            / "/*" (!"*/" [_])* "*/"
+            / block_comment()
+
+        /// Block comments nest, so `/* a /* b */ c */` is a single comment
+        rule block_comment()
+            = "/*" (block_comment() / !"*/" [_])* "*/"
     }
 }
 
@@ -402,6 +523,79 @@ pub fn evaluate(expr: &Expr) -> Result<f64, String> {
     }
 }
 
+fn spanned_binary(
+    src: &str,
+    op_pos: usize,
+    left: SpannedExpr,
+    op: BinaryOp,
+    right: SpannedExpr,
+) -> SpannedExpr {
+    SpannedExpr {
+        kind: SpannedExprKind::Binary {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        },
+        position: peg::Parse::position_repr(src, op_pos),
+    }
+}
+
+/// Like `evaluate`, but errors carry the position of the node that failed, so
+/// a division by zero points at its `/`.
+pub fn eval_spanned(expr: &SpannedExpr) -> Result<f64, EvalError> {
+    let error = |message: String| EvalError {
+        message,
+        position: expr.position,
+    };
+
+    match &expr.kind {
+        SpannedExprKind::Number(n) => Ok(*n as f64),
+        SpannedExprKind::Float(f) => Ok(*f),
+        SpannedExprKind::Identifier(name) => Err(error(format!("Unbound variable {}", name))),
+        SpannedExprKind::Binary { left, op, right } => {
+            let l = eval_spanned(left)?;
+            let r = eval_spanned(right)?;
+            match op {
+                BinaryOp::Add => Ok(l + r),
+                BinaryOp::Sub => Ok(l - r),
+                BinaryOp::Mul => Ok(l * r),
+                BinaryOp::Div => {
+                    if r == 0.0 {
+                        Err(error("Division by zero".to_string()))
+                    } else {
+                        Ok(l / r)
+                    }
+                }
+                BinaryOp::Mod => {
+                    if r == 0.0 {
+                        Err(error("Division by zero".to_string()))
+                    } else {
+                        Ok(l % r)
+                    }
+                }
+                BinaryOp::Pow => Ok(l.powf(r)),
+                _ => Err(error(format!("Cannot evaluate operator {:?}", op))),
+            }
+        }
+        SpannedExprKind::Unary {
+            op: UnaryOp::Neg,
+            expr,
+        } => Ok(-eval_spanned(expr)?),
+        SpannedExprKind::Unary { op, .. } => {
+            Err(error(format!("Cannot evaluate operator {:?}", op)))
+        }
+    }
+}
+
+/// Count the consecutive applications wrapping an expression, so `f(1)(2)` has
+/// arity 2 and `f()` has arity 1.
+pub fn arity_of(expr: &Expr) -> usize {
+    match expr {
+        Expr::Call { func, .. } => 1 + arity_of(func),
+        _ => 0,
+    }
+}
+
 /// Parse a simple expression
 pub fn parse_expression(input: &str) -> Result<Expr, peg::error::ParseError<peg::str::LineCol>> {
     functional_parser::expression(input)
@@ -412,6 +606,13 @@ pub fn parse_program(input: &str) -> Result<Program, peg::error::ParseError<peg:
     functional_parser::program(input)
 }
 
+/// Parse a sequence of arithmetic expressions with source positions
+pub fn parse_program_spanned(
+    input: &str,
+) -> Result<Vec<SpannedExpr>, peg::error::ParseError<peg::str::LineCol>> {
+    functional_parser::program_spanned(input, input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -497,6 +698,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_zero_argument_call() {
+        let result = parse_expression("f()").unwrap();
+        assert_eq!(
+            result,
+            Expr::Call {
+                func: Box::new(Expr::Identifier("f".to_string())),
+                args: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_curried_call() {
+        let result = parse_expression("f(1)(2)").unwrap();
+        assert_eq!(
+            result,
+            Expr::Call {
+                func: Box::new(Expr::Call {
+                    func: Box::new(Expr::Identifier("f".to_string())),
+                    args: vec![Expr::Number(1)],
+                }),
+                args: vec![Expr::Number(2)],
+            }
+        );
+    }
+
+    #[test]
+    fn test_arity_of() {
+        assert_eq!(arity_of(&parse_expression("f").unwrap()), 0);
+        assert_eq!(arity_of(&parse_expression("f()").unwrap()), 1);
+        assert_eq!(arity_of(&parse_expression("f(1)(2)").unwrap()), 2);
+        assert_eq!(arity_of(&parse_expression("f(1, 2)()(3)").unwrap()), 3);
+    }
+
     #[test]
     fn test_string_literals() {
         let result = parse_expression("\"hello world\"").unwrap();
@@ -518,13 +754,45 @@ mod tests {
         assert_eq!(result, Expr::List(vec![]));
     }
 
+    #[test]
+    fn test_record_update() {
+        let result = parse_expression("{ base with a: 1, b: 2 }").unwrap();
+        assert_eq!(
+            result,
+            Expr::RecordUpdate {
+                base: Box::new(Expr::Identifier("base".to_string())),
+                fields: vec![
+                    ("a".to_string(), Expr::Number(1)),
+                    ("b".to_string(), Expr::Number(2)),
+                ],
+            }
+        );
+
+        // The base can itself be a record literal
+        let result = parse_expression("{ { a: 0 } with a: 1 }").unwrap();
+        let Expr::RecordUpdate { base, fields } = result else {
+            panic!("Expected record update");
+        };
+        assert_eq!(
+            *base,
+            Expr::Record(HashMap::from([("a".to_string(), Expr::Number(0))]))
+        );
+        assert_eq!(fields, vec![("a".to_string(), Expr::Number(1))]);
+
+        // Plain records still parse, and `with` is no longer an identifier
+        assert!(matches!(parse_expression("{ x: 1 }"), Ok(Expr::Record(_))));
+        assert!(parse_expression("{ r with }").is_err());
+        assert!(parse_expression("with").is_err());
+    }
+
     #[test]
     fn test_let_expression() {
         let result = parse_expression("let x = 5 in x + 1").unwrap();
         if let Expr::Let { bindings, body } = result {
             assert_eq!(bindings.len(), 1);
             assert_eq!(bindings[0].0, "x");
-            assert_eq!(bindings[0].1, Expr::Number(5));
+            assert_eq!(bindings[0].1, None);
+            assert_eq!(bindings[0].2, Expr::Number(5));
 
             if let Expr::Binary { left, op, right } = &*body {
                 assert_eq!(**left, Expr::Identifier("x".to_string()));
@@ -575,9 +843,81 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_nested_block_comment() {
+        let plain = parse_program("def y = 2").unwrap();
+
+        let commented = parse_program("/* outer /* def x = 1 */ still outer */ def y = 2").unwrap();
+        assert_eq!(commented, plain);
+
+        let wrapped = parse_program("def y = 2 /* a /* b /* c */ */ */").unwrap();
+        assert_eq!(wrapped, plain);
+
+        assert!(parse_program("/* a /* b */ def y = 2").is_err());
+    }
+
     #[test]
     fn test_error_reporting() {
         let result = parse_expression("2 + ");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_annotated_definition() {
+        let program =
+            parse_program("def x: Int = 5\ndef f: Int -> [Int] = \\n -> [n]\ndef y = 1").unwrap();
+        let types: Vec<_> = program
+            .statements
+            .iter()
+            .map(|s| match s {
+                Statement::Definition { ty, .. } => ty.clone(),
+                _ => panic!("Expected definition"),
+            })
+            .collect();
+
+        let int = || TypeExpr::Named("Int".to_string());
+        assert_eq!(
+            types,
+            vec![
+                Some(int()),
+                Some(TypeExpr::Function(
+                    Box::new(int()),
+                    Box::new(TypeExpr::List(Box::new(int())))
+                )),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_annotated_let() {
+        let result = parse_expression("let y: Float = 1.0, z = 2 in y").unwrap();
+        if let Expr::Let { bindings, .. } = result {
+            assert_eq!(
+                bindings[0],
+                (
+                    "y".to_string(),
+                    Some(TypeExpr::Named("Float".to_string())),
+                    Expr::Float(1.0)
+                )
+            );
+            assert_eq!(bindings[1], ("z".to_string(), None, Expr::Number(2)));
+        } else {
+            panic!("Expected let expression");
+        }
+    }
+
+    #[test]
+    fn test_eval_spanned_division_by_zero() {
+        let exprs = parse_program_spanned("1 / 0").unwrap();
+        let err = eval_spanned(&exprs[0]).unwrap_err();
+        assert_eq!(err.message, "Division by zero");
+        assert_eq!((err.position.line, err.position.column), (1, 3));
+
+        // The error is reported at the inner division, not the enclosing sum
+        let exprs = parse_program_spanned("2 * 3\n1 + (4 /\n  (2 - 2))").unwrap();
+        assert_eq!(eval_spanned(&exprs[0]), Ok(6.0));
+        let err = eval_spanned(&exprs[1]).unwrap_err();
+        assert_eq!(err.to_string(), "2:8: Division by zero");
+    }
 }
@@ -105,8 +105,12 @@ fn main() {
             println!("  Parsed program successfully:");
             for statement in &program.statements {
                 match statement {
-                    Statement::Definition { name, value } => {
-                        println!("    def {} = {:?}", name, value);
+                    Statement::Definition { name, ty, value } => {
+                        if let Some(ty) = ty {
+                            println!("    def {}: {:?} = {:?}", name, ty, value);
+                        } else {
+                            println!("    def {} = {:?}", name, value);
+                        }
                     }
                     Statement::Expression(expr) => {
                         println!("    expr: {:?}", expr);
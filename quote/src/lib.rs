@@ -154,6 +154,20 @@ impl ToTokens for Literal {
 // Builder Pattern Generation
 
 pub fn generate_builder(struct_name: &str, fields: &[(String, String)]) -> TokenStream {
+    builder_tokens(struct_name, fields, false)
+}
+
+/// Like `generate_builder`, but each setter accepts `impl Into<T>` so callers
+/// can pass a `&str` where the field is a `String`.
+pub fn generate_builder_into(struct_name: &str, fields: &[(String, String)]) -> TokenStream {
+    builder_tokens(struct_name, fields, true)
+}
+
+fn builder_tokens(
+    struct_name: &str,
+    fields: &[(String, String)],
+    into_setters: bool,
+) -> TokenStream {
     let struct_ident = format_ident!("{}", struct_name);
     let builder_ident = format_ident!("{}Builder", struct_name);
 
@@ -172,10 +186,19 @@ pub fn generate_builder(struct_name: &str, fields: &[(String, String)]) -> Token
     let builder_methods = fields.iter().map(|(name, ty)| {
         let name = format_ident!("{}", name);
         let ty = format_ident!("{}", ty);
-        quote! {
-            pub fn #name(mut self, value: #ty) -> Self {
-                self.#name = Some(value);
-                self
+        if into_setters {
+            quote! {
+                pub fn #name(mut self, value: impl Into<#ty>) -> Self {
+                    self.#name = Some(value.into());
+                    self
+                }
+            }
+        } else {
+            quote! {
+                pub fn #name(mut self, value: #ty) -> Self {
+                    self.#name = Some(value);
+                    self
+                }
             }
         }
     });
@@ -250,6 +273,65 @@ pub fn generate_enum_matcher(enum_name: &str, variants: &[String]) -> TokenStrea
     }
 }
 
+/// Generates a `{Enum}Visitor` trait with one `visit_{variant}` method per
+/// variant, plus a provided `walk` method that dispatches on the variant.
+/// Method names are the snake_case form of the variant names.
+pub fn generate_visitor_trait(enum_name: &str, variants: &[String]) -> TokenStream {
+    let enum_ident = format_ident!("{}", enum_name);
+    let trait_ident = format_ident!("{}Visitor", enum_name);
+    let method_idents: Vec<_> = variants
+        .iter()
+        .map(|variant| format_ident!("visit_{}", to_snake_case(variant)))
+        .collect();
+    let methods = method_idents.iter().map(|method| {
+        quote! {
+            fn #method(&mut self, node: &#enum_ident);
+        }
+    });
+    let walk_arms = variants
+        .iter()
+        .zip(&method_idents)
+        .map(|(variant, method)| {
+            let variant_ident = format_ident!("{}", variant);
+            quote! {
+                #enum_ident::#variant_ident { .. } => self.#method(node)
+            }
+        });
+
+    quote! {
+        pub trait #trait_ident {
+            #(#methods)*
+
+            fn walk(&mut self, node: &#enum_ident) {
+                match node {
+                    #(#walk_arms),*
+                }
+            }
+        }
+    }
+}
+
+/// Converts a CamelCase name to snake_case, keeping acronyms together
+/// (`BinaryOp` becomes `binary_op`, `IOError` becomes `io_error`).
+fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_uppercase() && next_is_lower)
+            {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
 // Trait Implementation Generation
 
 pub fn generate_display_impl(
@@ -272,6 +354,169 @@ pub fn generate_display_impl(
     }
 }
 
+/// Generates a `Display` impl for an enum from `(variant, format_str)` pairs.
+/// A variant is either a bare name (`Unit`) or a tuple pattern whose bindings
+/// are passed to `write!` in order (`Pair(a, b)`).
+pub fn generate_enum_display(enum_name: &str, variants: &[(String, String)]) -> TokenStream {
+    let enum_ident = format_ident!("{}", enum_name);
+    let match_arms = variants.iter().map(|(variant, format_str)| {
+        let (name, bindings) = match variant.split_once('(') {
+            Some((name, rest)) => {
+                let bindings: Vec<_> = rest
+                    .trim_end_matches(')')
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|b| !b.is_empty())
+                    .map(|b| format_ident!("{}", b))
+                    .collect();
+                (name.trim(), bindings)
+            }
+            None => (variant.trim(), Vec::new()),
+        };
+        let variant_ident = format_ident!("{}", name);
+
+        if bindings.is_empty() {
+            quote! {
+                #enum_ident::#variant_ident => write!(f, #format_str)
+            }
+        } else {
+            quote! {
+                #enum_ident::#variant_ident(#(#bindings),*) => write!(f, #format_str, #(#bindings),*)
+            }
+        }
+    });
+
+    quote! {
+        impl std::fmt::Display for #enum_ident {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    #(#match_arms),*
+                }
+            }
+        }
+    }
+}
+
+// Conversion Impl Generation
+
+/// Generates `impl From<from_ty> for to_ty`. Each `(target, source)` pair in
+/// `field_maps` assigns `target` from `value.source`, in the order given.
+pub fn generate_from_impl(
+    from_ty: &str,
+    to_ty: &str,
+    field_maps: &[(String, String)],
+) -> TokenStream {
+    let from_ident = format_ident!("{}", from_ty);
+    let to_ident = format_ident!("{}", to_ty);
+    let assigns = field_maps.iter().map(|(target, source)| {
+        let target = format_ident!("{}", target);
+        let source = format_ident!("{}", source);
+        quote! { #target: value.#source.into() }
+    });
+
+    quote! {
+        impl From<#from_ident> for #to_ident {
+            fn from(value: #from_ident) -> Self {
+                #to_ident {
+                    #(#assigns),*
+                }
+            }
+        }
+    }
+}
+
+/// Fallible counterpart of `generate_from_impl`. Each field goes through
+/// `TryInto`, and the first failing field is reported by name.
+pub fn generate_try_from_impl(
+    from_ty: &str,
+    to_ty: &str,
+    field_maps: &[(String, String)],
+) -> TokenStream {
+    let from_ident = format_ident!("{}", from_ty);
+    let to_ident = format_ident!("{}", to_ty);
+    let assigns = field_maps.iter().map(|(target, source)| {
+        let target_ident = format_ident!("{}", target);
+        let source_ident = format_ident!("{}", source);
+        let error_msg = format!("Cannot convert field {} into {}", source, target);
+        quote! {
+            #target_ident: value.#source_ident.try_into().map_err(|_| #error_msg.to_string())?
+        }
+    });
+
+    quote! {
+        impl TryFrom<#from_ident> for #to_ident {
+            type Error = String;
+
+            fn try_from(value: #from_ident) -> Result<Self, Self::Error> {
+                Ok(#to_ident {
+                    #(#assigns),*
+                })
+            }
+        }
+    }
+}
+
+/// Generates a `to_map` method that collects every field into a
+/// `HashMap<String, String>` keyed by field name. Values go through
+/// `to_string()`, so any field type implementing `Display` works, including
+/// `String` and the numeric primitives.
+pub fn generate_to_map_impl(struct_name: &str, fields: &[(String, String)]) -> TokenStream {
+    let struct_ident = format_ident!("{}", struct_name);
+    let inserts = fields.iter().map(|(name, _)| {
+        let field = format_ident!("{}", name);
+        quote! {
+            map.insert(#name.to_string(), self.#field.to_string());
+        }
+    });
+
+    quote! {
+        impl #struct_ident {
+            pub fn to_map(&self) -> std::collections::HashMap<String, String> {
+                let mut map = std::collections::HashMap::new();
+                #(#inserts)*
+                map
+            }
+        }
+    }
+}
+
+/// Generates a tuple newtype around `inner` that derefs to it and converts to
+/// and from it with `From`.
+pub fn generate_newtype(name: &str, inner: &str) -> TokenStream {
+    let name_ident = format_ident!("{}", name);
+    let inner_ident = format_ident!("{}", inner);
+
+    quote! {
+        pub struct #name_ident(#inner_ident);
+
+        impl std::ops::Deref for #name_ident {
+            type Target = #inner_ident;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl std::ops::DerefMut for #name_ident {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+
+        impl From<#inner_ident> for #name_ident {
+            fn from(value: #inner_ident) -> Self {
+                #name_ident(value)
+            }
+        }
+
+        impl From<#name_ident> for #inner_ident {
+            fn from(value: #name_ident) -> Self {
+                value.0
+            }
+        }
+    }
+}
+
 // Repetition and Interpolation
 
 pub fn generate_vector_wrapper(item_type: &str, methods: &[(&str, &str)]) -> TokenStream {
@@ -487,6 +732,23 @@ mod tests {
         assert!(output.contains("value : u32"));
     }
 
+    #[test]
+    fn test_builder_into_generation() {
+        let fields = vec![
+            ("name".to_string(), "String".to_string()),
+            ("age".to_string(), "u32".to_string()),
+        ];
+
+        let tokens = generate_builder_into("Person", &fields);
+        let output = tokens.to_string();
+        assert!(output.contains("value : impl Into < String >"));
+        assert!(output.contains("value : impl Into < u32 >"));
+        assert!(output.contains("Some (value . into ())"));
+        // build() still unwraps each Option
+        assert!(output.contains("self . name . ok_or"));
+        assert!(output.contains("self . age . ok_or"));
+    }
+
     #[test]
     fn test_enum_matcher() {
         let variants = vec!["Red".to_string(), "Green".to_string(), "Blue".to_string()];
@@ -521,6 +783,116 @@ mod tests {
         assert!(output.contains("self.y") || output.contains("self . y"));
     }
 
+    #[test]
+    fn test_enum_display() {
+        let variants = vec![
+            ("Empty".to_string(), "empty".to_string()),
+            ("Pair(a, b)".to_string(), "({}, {})".to_string()),
+        ];
+        let output = generate_enum_display("Shape", &variants).to_string();
+
+        assert!(output.contains("impl std :: fmt :: Display for Shape"));
+        assert!(output.contains("Shape :: Empty => write ! (f , \"empty\")"));
+        assert!(output.contains("Shape :: Pair (a , b) => write ! (f , \"({}, {})\" , a , b)"));
+    }
+
+    #[test]
+    fn test_from_impl() {
+        let maps = vec![
+            ("id".to_string(), "user_id".to_string()),
+            ("label".to_string(), "name".to_string()),
+        ];
+        let output = generate_from_impl("UserRow", "User", &maps).to_string();
+
+        assert!(output.contains("impl From < UserRow > for User"));
+        assert!(output.contains("fn from (value : UserRow) -> Self"));
+        let id = output.find("id : value . user_id . into ()").unwrap();
+        let label = output.find("label : value . name . into ()").unwrap();
+        assert!(id < label);
+    }
+
+    #[test]
+    fn test_try_from_impl() {
+        let maps = vec![
+            ("small".to_string(), "big".to_string()),
+            ("flag".to_string(), "raw_flag".to_string()),
+        ];
+        let output = generate_try_from_impl("Wide", "Narrow", &maps).to_string();
+
+        assert!(output.contains("impl TryFrom < Wide > for Narrow"));
+        assert!(output.contains("type Error = String"));
+        assert!(output.contains("Result < Self , Self :: Error >"));
+        let small = output.find("small : value . big . try_into ()").unwrap();
+        let flag = output
+            .find("flag : value . raw_flag . try_into ()")
+            .unwrap();
+        assert!(small < flag);
+    }
+
+    #[test]
+    fn test_to_map_impl() {
+        let fields = vec![
+            ("name".to_string(), "String".to_string()),
+            ("age".to_string(), "u32".to_string()),
+            ("score".to_string(), "f64".to_string()),
+        ];
+        let tokens = generate_to_map_impl("Person", &fields);
+        let output = tokens.to_string();
+
+        assert!(output.contains("impl Person"));
+        assert!(output
+            .contains("fn to_map (& self) -> std :: collections :: HashMap < String , String >"));
+        assert_eq!(output.matches("map . insert").count(), fields.len());
+        for (name, _) in &fields {
+            let insert = format!(
+                "map . insert (\"{}\" . to_string () , self . {} . to_string ())",
+                name, name
+            );
+            assert!(output.contains(&insert), "missing insert for {}", name);
+        }
+    }
+
+    #[test]
+    fn test_visitor_trait() {
+        let variants = vec![
+            "Literal".to_string(),
+            "BinaryOp".to_string(),
+            "IfElse".to_string(),
+            "IOError".to_string(),
+        ];
+        let tokens = generate_visitor_trait("Expr", &variants);
+        let output = tokens.to_string();
+
+        assert!(output.contains("pub trait ExprVisitor"));
+        assert_eq!(
+            output.matches("(& mut self , node : & Expr) ;").count(),
+            variants.len()
+        );
+        for method in [
+            "visit_literal",
+            "visit_binary_op",
+            "visit_if_else",
+            "visit_io_error",
+        ] {
+            let decl = format!("fn {} (& mut self , node : & Expr) ;", method);
+            assert!(output.contains(&decl), "missing {}", method);
+        }
+        assert!(output.contains("fn walk (& mut self , node : & Expr)"));
+        assert!(output.contains("Expr :: BinaryOp { .. } => self . visit_binary_op (node)"));
+    }
+
+    #[test]
+    fn test_newtype() {
+        let tokens = generate_newtype("UserId", "u64");
+        let output = tokens.to_string();
+
+        assert!(output.contains("pub struct UserId (u64) ;"));
+        assert!(output.contains("impl std :: ops :: Deref for UserId { type Target = u64 ;"));
+        assert!(output.contains("impl std :: ops :: DerefMut for UserId"));
+        assert!(output.contains("impl From < u64 > for UserId"));
+        assert!(output.contains("impl From < UserId > for u64"));
+    }
+
     #[test]
     fn test_generic_struct() {
         let type_params = vec!["T".to_string(), "U".to_string()];
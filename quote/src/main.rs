@@ -1,7 +1,8 @@
 use quote::quote;
 use quote_example::{
-    generate_builder, generate_conditional_impl, generate_derives, generate_display_impl,
-    generate_enum_matcher, generate_generic_struct, generate_method_chain, generate_unrolled_loop,
+    generate_builder, generate_builder_into, generate_conditional_impl, generate_derives,
+    generate_display_impl, generate_enum_display, generate_enum_matcher, generate_from_impl,
+    generate_generic_struct, generate_method_chain, generate_try_from_impl, generate_unrolled_loop,
     generate_vector_wrapper, BinaryOp, Expr, Function, Literal, Parameter, Statement,
 };
 
@@ -55,6 +56,12 @@ fn main() {
     let builder_tokens = generate_builder("User", &fields);
     println!("{}\n", builder_tokens);
 
+    // Generate a builder whose setters accept anything convertible into the field
+    // type
+    println!("Generated Builder with Into Setters:");
+    let into_builder_tokens = generate_builder_into("User", &fields);
+    println!("{}\n", into_builder_tokens);
+
     // Generate enum matcher
     println!("Generated Enum Matcher:");
     let variants = vec![
@@ -76,6 +83,27 @@ fn main() {
     );
     println!("{}\n", display_tokens);
 
+    // Generate Display for an enum
+    println!("Generated Enum Display Implementation:");
+    let display_variants = vec![
+        ("Eof".to_string(), "end of file".to_string()),
+        ("Ident(name)".to_string(), "identifier `{}`".to_string()),
+        ("Number(value)".to_string(), "number {}".to_string()),
+    ];
+    let enum_display_tokens = generate_enum_display("Token", &display_variants);
+    println!("{}\n", enum_display_tokens);
+
+    // Generate conversion implementations
+    println!("Generated From/TryFrom Implementations:");
+    let field_maps = vec![
+        ("id".to_string(), "user_id".to_string()),
+        ("name".to_string(), "display_name".to_string()),
+    ];
+    let from_tokens = generate_from_impl("UserRecord", "User", &field_maps);
+    println!("{}\n", from_tokens);
+    let try_from_tokens = generate_try_from_impl("UserRecord", "User", &field_maps);
+    println!("{}\n", try_from_tokens);
+
     // Generate generic struct
     println!("Generated Generic Struct:");
     let type_params = vec!["K".to_string(), "V".to_string()];
@@ -1,4 +1,12 @@
-use rowan::{GreenNode, GreenNodeBuilder, Language, SyntaxNode, SyntaxToken, TextRange, TextSize};
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::str::Chars;
+
+use rowan::{
+    GreenNode, GreenNodeBuilder, Language, NodeOrToken, SyntaxNode, SyntaxToken, TextRange,
+    TextSize,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(u16)]
@@ -22,6 +30,12 @@ pub enum SyntaxKind {
     RBrace,
     Semicolon,
     Comma,
+    LBracket,
+    RBracket,
+    Dot,
+    StringStart,
+    StringMiddle,
+    StringEnd,
     Keyword,
     Error,
     Root,
@@ -41,6 +55,9 @@ pub enum SyntaxKind {
     Path,
     CallExpr,
     ArgList,
+    IndexExpr,
+    FieldExpr,
+    InterpolatedString,
 }
 
 impl From<SyntaxKind> for rowan::SyntaxKind {
@@ -56,7 +73,7 @@ impl Language for Lang {
     type Kind = SyntaxKind;
 
     fn kind_from_raw(raw: rowan::SyntaxKind) -> Self::Kind {
-        assert!(raw.0 <= SyntaxKind::ArgList as u16);
+        assert!(raw.0 <= SyntaxKind::InterpolatedString as u16);
         unsafe { std::mem::transmute::<u16, SyntaxKind>(raw.0) }
     }
 
@@ -149,17 +166,17 @@ impl Parser {
     fn let_statement(&mut self) {
         self.builder.start_node(SyntaxKind::LetStmt.into());
         self.consume(SyntaxKind::Keyword);
-        self.skip_trivia();
+        self.trivia();
         self.consume(SyntaxKind::Ident);
-        self.skip_trivia();
+        self.trivia();
 
         if self.at(SyntaxKind::Eq) {
             self.consume(SyntaxKind::Eq);
-            self.skip_trivia();
+            self.trivia();
             self.expression();
         }
 
-        self.skip_trivia();
+        self.trivia();
         self.consume(SyntaxKind::Semicolon);
         self.builder.finish_node();
     }
@@ -167,14 +184,14 @@ impl Parser {
     fn if_statement(&mut self) {
         self.builder.start_node(SyntaxKind::IfStmt.into());
         self.consume(SyntaxKind::Keyword);
-        self.skip_trivia();
+        self.trivia();
         self.expression();
-        self.skip_trivia();
+        self.trivia();
         self.block();
 
         if self.at_keyword("else") {
             self.consume(SyntaxKind::Keyword);
-            self.skip_trivia();
+            self.trivia();
             if self.at_keyword("if") {
                 self.if_statement();
             } else {
@@ -188,9 +205,9 @@ impl Parser {
     fn while_statement(&mut self) {
         self.builder.start_node(SyntaxKind::WhileStmt.into());
         self.consume(SyntaxKind::Keyword);
-        self.skip_trivia();
+        self.trivia();
         self.expression();
-        self.skip_trivia();
+        self.trivia();
         self.block();
         self.builder.finish_node();
     }
@@ -198,13 +215,13 @@ impl Parser {
     fn return_statement(&mut self) {
         self.builder.start_node(SyntaxKind::ReturnStmt.into());
         self.consume(SyntaxKind::Keyword);
-        self.skip_trivia();
+        self.trivia();
 
         if !self.at(SyntaxKind::Semicolon) {
             self.expression();
         }
 
-        self.skip_trivia();
+        self.trivia();
         self.consume(SyntaxKind::Semicolon);
         self.builder.finish_node();
     }
@@ -212,11 +229,11 @@ impl Parser {
     fn function_definition(&mut self) {
         self.builder.start_node(SyntaxKind::FnDef.into());
         self.consume(SyntaxKind::Keyword);
-        self.skip_trivia();
+        self.trivia();
         self.consume(SyntaxKind::Ident);
-        self.skip_trivia();
+        self.trivia();
         self.parameter_list();
-        self.skip_trivia();
+        self.trivia();
         self.block();
         self.builder.finish_node();
     }
@@ -224,16 +241,16 @@ impl Parser {
     fn parameter_list(&mut self) {
         self.builder.start_node(SyntaxKind::ParamList.into());
         self.consume(SyntaxKind::LParen);
-        self.skip_trivia();
+        self.trivia();
 
         if !self.at(SyntaxKind::RParen) {
             loop {
                 self.consume(SyntaxKind::Ident);
-                self.skip_trivia();
+                self.trivia();
 
                 if self.at(SyntaxKind::Comma) {
                     self.consume(SyntaxKind::Comma);
-                    self.skip_trivia();
+                    self.trivia();
                 } else {
                     break;
                 }
@@ -263,7 +280,7 @@ impl Parser {
     fn expression_statement(&mut self) {
         self.builder.start_node(SyntaxKind::ExprStmt.into());
         self.expression();
-        self.skip_trivia();
+        self.trivia();
         self.consume(SyntaxKind::Semicolon);
         self.builder.finish_node();
     }
@@ -273,6 +290,8 @@ impl Parser {
     }
 
     fn binary_expression(&mut self, min_precedence: u8) {
+        // Taken before the left operand so each BinaryExpr wraps both sides
+        let checkpoint = self.builder.checkpoint();
         self.unary_expression();
 
         // Include whitespace in the tree
@@ -285,8 +304,6 @@ impl Parser {
                 break;
             }
 
-            let checkpoint = self.builder.checkpoint();
-
             if let Some(
                 k @ (SyntaxKind::Plus
                 | SyntaxKind::Minus
@@ -329,14 +346,35 @@ impl Parser {
     }
 
     fn postfix_expression(&mut self) {
+        let checkpoint = self.builder.checkpoint();
         self.primary_expression();
 
-        while self.at(SyntaxKind::LParen) {
-            self.builder.start_node(SyntaxKind::CallExpr.into());
-            let checkpoint = self.builder.checkpoint();
-            self.argument_list();
-            self.builder
-                .start_node_at(checkpoint, SyntaxKind::CallExpr.into());
+        // Each suffix wraps everything parsed so far, so `a.b[0](x)` nests
+        // left-to-right as Call(Index(Field(a, b), 0), x)
+        loop {
+            match self.current_kind() {
+                Some(SyntaxKind::LParen) => {
+                    self.builder
+                        .start_node_at(checkpoint, SyntaxKind::CallExpr.into());
+                    self.argument_list();
+                }
+                Some(SyntaxKind::LBracket) => {
+                    self.builder
+                        .start_node_at(checkpoint, SyntaxKind::IndexExpr.into());
+                    self.consume(SyntaxKind::LBracket);
+                    self.trivia();
+                    self.expression();
+                    self.trivia();
+                    self.consume(SyntaxKind::RBracket);
+                }
+                Some(SyntaxKind::Dot) => {
+                    self.builder
+                        .start_node_at(checkpoint, SyntaxKind::FieldExpr.into());
+                    self.consume(SyntaxKind::Dot);
+                    self.consume(SyntaxKind::Ident);
+                }
+                _ => break,
+            }
             self.builder.finish_node();
         }
     }
@@ -344,16 +382,16 @@ impl Parser {
     fn argument_list(&mut self) {
         self.builder.start_node(SyntaxKind::ArgList.into());
         self.consume(SyntaxKind::LParen);
-        self.skip_trivia();
+        self.trivia();
 
         if !self.at(SyntaxKind::RParen) {
             loop {
                 self.expression();
-                self.skip_trivia();
+                self.trivia();
 
                 if self.at(SyntaxKind::Comma) {
                     self.consume(SyntaxKind::Comma);
-                    self.skip_trivia();
+                    self.trivia();
                 } else {
                     break;
                 }
@@ -376,6 +414,9 @@ impl Parser {
                 self.consume(SyntaxKind::String);
                 self.builder.finish_node();
             }
+            Some(SyntaxKind::StringStart) => {
+                self.interpolated_string();
+            }
             Some(SyntaxKind::Ident) => {
                 self.consume(SyntaxKind::Ident);
             }
@@ -399,6 +440,27 @@ impl Parser {
         }
     }
 
+    fn interpolated_string(&mut self) {
+        self.builder
+            .start_node(SyntaxKind::InterpolatedString.into());
+        self.consume(SyntaxKind::StringStart);
+
+        loop {
+            self.trivia();
+            self.expression();
+            self.trivia();
+
+            if self.at(SyntaxKind::StringMiddle) {
+                self.consume(SyntaxKind::StringMiddle);
+            } else {
+                self.consume(SyntaxKind::StringEnd);
+                break;
+            }
+        }
+
+        self.builder.finish_node();
+    }
+
     fn current_binary_op_precedence(&self) -> Option<u8> {
         match self.current_kind()? {
             SyntaxKind::Star | SyntaxKind::Slash => Some(5),
@@ -416,12 +478,6 @@ impl Parser {
         }
     }
 
-    fn skip_trivia(&mut self) {
-        while self.at(SyntaxKind::Whitespace) || self.at(SyntaxKind::Comment) {
-            self.advance();
-        }
-    }
-
     fn consume(&mut self, expected: SyntaxKind) {
         if self.at(expected) {
             let token = &self.tokens[self.cursor];
@@ -476,6 +532,8 @@ pub fn tokenize(input: &str) -> Vec<Token> {
     let mut tokens = Vec::new();
     let mut offset = TextSize::from(0);
     let mut chars = input.chars().peekable();
+    // Brace depth of each open `${...}`, innermost last
+    let mut interpolations: Vec<usize> = Vec::new();
 
     while let Some(ch) = chars.next() {
         let start = offset;
@@ -529,21 +587,42 @@ pub fn tokenize(input: &str) -> Vec<Token> {
             '>' => (SyntaxKind::Gt, String::from(">")),
             '(' => (SyntaxKind::LParen, String::from("(")),
             ')' => (SyntaxKind::RParen, String::from(")")),
-            '{' => (SyntaxKind::LBrace, String::from("{")),
-            '}' => (SyntaxKind::RBrace, String::from("}")),
+            '{' => {
+                if let Some(depth) = interpolations.last_mut() {
+                    *depth += 1;
+                }
+                (SyntaxKind::LBrace, String::from("{"))
+            }
+            '}' if interpolations.last() == Some(&0) => {
+                // Closes the interpolation, so resume scanning the string
+                interpolations.pop();
+                let mut text = String::from("}");
+                if scan_string_segment(&mut chars, &mut offset, &mut text) {
+                    interpolations.push(0);
+                    (SyntaxKind::StringMiddle, text)
+                } else {
+                    (SyntaxKind::StringEnd, text)
+                }
+            }
+            '}' => {
+                if let Some(depth) = interpolations.last_mut() {
+                    *depth -= 1;
+                }
+                (SyntaxKind::RBrace, String::from("}"))
+            }
             ';' => (SyntaxKind::Semicolon, String::from(";")),
             ',' => (SyntaxKind::Comma, String::from(",")),
+            '[' => (SyntaxKind::LBracket, String::from("[")),
+            ']' => (SyntaxKind::RBracket, String::from("]")),
+            '.' => (SyntaxKind::Dot, String::from(".")),
             '"' => {
                 let mut text = String::from("\"");
-                while let Some(&next) = chars.peek() {
-                    text.push(next);
-                    offset += TextSize::of(next);
-                    chars.next();
-                    if next == '"' {
-                        break;
-                    }
+                if scan_string_segment(&mut chars, &mut offset, &mut text) {
+                    interpolations.push(0);
+                    (SyntaxKind::StringStart, text)
+                } else {
+                    (SyntaxKind::String, text)
                 }
-                (SyntaxKind::String, text)
             }
             c if c.is_ascii_digit() => {
                 let mut text = String::from(c);
@@ -589,6 +668,33 @@ pub fn tokenize(input: &str) -> Vec<Token> {
     tokens
 }
 
+/// Scans string contents into `text` up to the closing quote or a `${`.
+///
+/// Returns true when the segment ends by opening an interpolation.
+fn scan_string_segment(
+    chars: &mut Peekable<Chars<'_>>,
+    offset: &mut TextSize,
+    text: &mut String,
+) -> bool {
+    while let Some(next) = chars.next() {
+        text.push(next);
+        *offset += TextSize::of(next);
+
+        match next {
+            '"' => return false,
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+                *offset += TextSize::of('{');
+                return true;
+            }
+            _ => {}
+        }
+    }
+
+    false
+}
+
 #[derive(Debug)]
 pub struct IncrementalReparser {
     _old_tree: SyntaxNodeRef,
@@ -620,6 +726,40 @@ impl IncrementalReparser {
     }
 }
 
+/// Apply a batch of edits to `source`, with every range referring to the
+/// original text. Edits are sorted by start offset and applied right to left
+/// so earlier offsets stay valid. Edits may touch but not overlap; an
+/// overlapping or out-of-bounds edit is an error.
+pub fn apply_edits(source: &str, edits: &[TextEdit]) -> Result<String, String> {
+    let mut sorted: Vec<&TextEdit> = edits.iter().collect();
+    sorted.sort_by_key(|edit| edit.range.start());
+
+    for pair in sorted.windows(2) {
+        if pair[1].range.start() < pair[0].range.end() {
+            return Err(format!(
+                "edits at {:?} and {:?} overlap",
+                pair[0].range, pair[1].range
+            ));
+        }
+    }
+
+    let mut text = source.to_string();
+    for edit in sorted.iter().rev() {
+        let range: Range<usize> = edit.range.into();
+        if range.end > text.len()
+            || !text.is_char_boundary(range.start)
+            || !text.is_char_boundary(range.end)
+        {
+            return Err(format!(
+                "edit at {:?} is outside the source text",
+                edit.range
+            ));
+        }
+        text.replace_range(range, &edit.new_text);
+    }
+    Ok(text)
+}
+
 pub struct SyntaxTreeBuilder {
     green: GreenNode,
 }
@@ -681,7 +821,10 @@ impl AstNode {
             | SyntaxKind::WhileStmt
             | SyntaxKind::ReturnStmt
             | SyntaxKind::FnDef
-            | SyntaxKind::CallExpr => Some(Self { syntax }),
+            | SyntaxKind::CallExpr
+            | SyntaxKind::IndexExpr
+            | SyntaxKind::FieldExpr
+            | SyntaxKind::InterpolatedString => Some(Self { syntax }),
             _ => None,
         }
     }
@@ -749,6 +892,88 @@ pub fn find_node_at_offset(root: &SyntaxNodeRef, offset: TextSize) -> Option<Syn
     Some(result)
 }
 
+/// Rebuild the tree with every `Ident` token spelled `old` respelled `new`.
+/// Keywords are separate tokens, and all other tokens and trivia are copied
+/// unchanged, so the new tree's text differs only at the renamed identifiers.
+pub fn rename_identifier(root: &SyntaxNodeRef, old: &str, new: &str) -> GreenNode {
+    let mut builder = GreenNodeBuilder::new();
+    rebuild_renamed(&mut builder, root, old, new);
+    builder.finish()
+}
+
+fn rebuild_renamed(builder: &mut GreenNodeBuilder, node: &SyntaxNodeRef, old: &str, new: &str) {
+    builder.start_node(node.kind().into());
+    for child in node.children_with_tokens() {
+        match child {
+            NodeOrToken::Node(child) => rebuild_renamed(builder, &child, old, new),
+            NodeOrToken::Token(token) => {
+                let text = if token.kind() == SyntaxKind::Ident && token.text() == old {
+                    new
+                } else {
+                    token.text()
+                };
+                builder.token(token.kind().into(), text);
+            }
+        }
+    }
+    builder.finish_node();
+}
+
+/// Start and end offsets of every block that spans more than one line, in
+/// document order. Each range runs from the `{` to just past the `}`.
+pub fn folding_ranges(root: &SyntaxNodeRef) -> Vec<(TextSize, TextSize)> {
+    root.descendants()
+        .filter(|node| node.kind() == SyntaxKind::BlockStmt)
+        .filter(|node| node.text().contains_char('\n'))
+        .map(|node| {
+            let range = node.text_range();
+            (range.start(), range.end())
+        })
+        .collect()
+}
+
+/// Shape statistics for a syntax tree. Whitespace and comment tokens are
+/// left out of every count, so reformatting a program does not change them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeMetrics {
+    /// Nodes plus non-trivia tokens.
+    pub node_count: usize,
+    /// Deepest node below the root, with the root itself at depth 0.
+    pub max_depth: usize,
+    pub kind_counts: HashMap<SyntaxKind, usize>,
+}
+
+impl TreeMetrics {
+    pub fn count(&self, kind: SyntaxKind) -> usize {
+        self.kind_counts.get(&kind).copied().unwrap_or(0)
+    }
+}
+
+pub fn tree_metrics(root: &SyntaxNodeRef) -> TreeMetrics {
+    let mut metrics = TreeMetrics::default();
+    collect_metrics(&mut metrics, root, 0);
+    metrics
+}
+
+fn collect_metrics(metrics: &mut TreeMetrics, node: &SyntaxNodeRef, depth: usize) {
+    metrics.node_count += 1;
+    metrics.max_depth = metrics.max_depth.max(depth);
+    *metrics.kind_counts.entry(node.kind()).or_insert(0) += 1;
+
+    for child in node.children_with_tokens() {
+        match child {
+            NodeOrToken::Node(child) => collect_metrics(metrics, &child, depth + 1),
+            NodeOrToken::Token(token) => {
+                if matches!(token.kind(), SyntaxKind::Whitespace | SyntaxKind::Comment) {
+                    continue;
+                }
+                metrics.node_count += 1;
+                *metrics.kind_counts.entry(token.kind()).or_insert(0) += 1;
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -811,6 +1036,30 @@ mod tests {
         assert_eq!(new_tree.errors.len(), 0);
     }
 
+    #[test]
+    fn test_apply_edits() {
+        let edit = |start: u32, end: u32, text: &str| TextEdit {
+            range: TextRange::new(TextSize::from(start), TextSize::from(end)),
+            new_text: text.to_string(),
+        };
+
+        let source = "let x = 42;";
+        assert_eq!(
+            apply_edits(source, &[edit(8, 10, "100")]),
+            Ok("let x = 100;".to_string())
+        );
+
+        // Given out of order; the second edit's offsets refer to the original text
+        assert_eq!(
+            apply_edits(source, &[edit(8, 10, "x + 1"), edit(4, 5, "total")]),
+            Ok("let total = x + 1;".to_string())
+        );
+
+        let result = apply_edits(source, &[edit(4, 9, "y"), edit(8, 10, "1")]);
+        assert_eq!(result, Err("edits at 4..9 and 8..10 overlap".to_string()));
+        assert!(apply_edits(source, &[edit(10, 20, "")]).is_err());
+    }
+
     #[test]
     fn test_ast_node_cast() {
         let input = "42 + x";
@@ -821,4 +1070,118 @@ mod tests {
             assert!(ast_node.is_some());
         }
     }
+
+    #[test]
+    fn test_postfix_chain_nesting() {
+        let input = "a.b[0](x)";
+        let tree = parse_expression(input);
+        assert_eq!(tree.text().to_string(), input);
+
+        let call = tree.first_child().unwrap();
+        assert_eq!(call.kind(), SyntaxKind::CallExpr);
+        assert_eq!(call.text().to_string(), "a.b[0](x)");
+
+        let index = call.first_child().unwrap();
+        assert_eq!(index.kind(), SyntaxKind::IndexExpr);
+        assert_eq!(index.text().to_string(), "a.b[0]");
+        assert_eq!(
+            call.children().nth(1).map(|node| node.kind()),
+            Some(SyntaxKind::ArgList)
+        );
+
+        let field = index.first_child().unwrap();
+        assert_eq!(field.kind(), SyntaxKind::FieldExpr);
+        assert_eq!(field.text().to_string(), "a.b");
+        assert_eq!(
+            index.children().nth(1).map(|node| node.kind()),
+            Some(SyntaxKind::Literal)
+        );
+    }
+
+    #[test]
+    fn test_interpolated_string() {
+        let input = "\"a ${1 + x} b\"";
+        let kinds: Vec<_> = tokenize(input).iter().map(|t| t.kind).collect();
+        assert_eq!(kinds.first(), Some(&SyntaxKind::StringStart));
+        assert_eq!(kinds.last(), Some(&SyntaxKind::StringEnd));
+
+        let tree = parse_expression(input);
+        assert_eq!(tree.text().to_string(), input);
+
+        let string = tree.first_child().unwrap();
+        assert_eq!(string.kind(), SyntaxKind::InterpolatedString);
+        let binary = string.first_child().unwrap();
+        assert_eq!(binary.kind(), SyntaxKind::BinaryExpr);
+        assert_eq!(binary.text().to_string().trim(), "1 + x");
+    }
+
+    #[test]
+    fn test_interpolation_brace_depth() {
+        let kinds: Vec<_> = tokenize("\"${ {1} }\"").iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SyntaxKind::StringStart,
+                SyntaxKind::Whitespace,
+                SyntaxKind::LBrace,
+                SyntaxKind::Number,
+                SyntaxKind::RBrace,
+                SyntaxKind::Whitespace,
+                SyntaxKind::StringEnd,
+            ]
+        );
+
+        let tokens = tokenize("\"${a}-${b}\"");
+        let texts: Vec<_> = tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(texts, vec!["\"${", "a", "}-${", "b", "}\""]);
+        assert_eq!(tokens[2].kind, SyntaxKind::StringMiddle);
+    }
+
+    #[test]
+    fn test_rename_identifier() {
+        let input = "let x = x + 1; // x stays\nlet xs = x;";
+        let tree = SyntaxNodeRef::new_root(Parser::new(tokenize(input)).parse().green_node);
+
+        let renamed = SyntaxNodeRef::new_root(rename_identifier(&tree, "x", "y"));
+        assert_eq!(
+            renamed.text().to_string(),
+            "let y = y + 1; // x stays\nlet xs = y;"
+        );
+
+        // Keywords are never identifiers, so they are left alone
+        let renamed = SyntaxNodeRef::new_root(rename_identifier(&tree, "let", "var"));
+        assert_eq!(renamed.text().to_string(), input);
+    }
+
+    #[test]
+    fn test_tree_metrics() {
+        let source = "if a { while b { if c { x; } } }";
+        let tree = SyntaxNodeRef::new_root(Parser::new(tokenize(source)).parse().green_node);
+        let metrics = tree_metrics(&tree);
+
+        // Root > If > Block > While > Block > If > Block > ExprStmt
+        assert_eq!(metrics.max_depth, 7);
+        assert_eq!(metrics.count(SyntaxKind::IfStmt), 2);
+        assert_eq!(metrics.count(SyntaxKind::WhileStmt), 1);
+        assert_eq!(metrics.count(SyntaxKind::Whitespace), 0);
+
+        let spaced = "if a {\n  while b {\n    if c { x; } // inner\n  }\n}";
+        let spaced_tree = SyntaxNodeRef::new_root(Parser::new(tokenize(spaced)).parse().green_node);
+        assert_eq!(tree_metrics(&spaced_tree), metrics);
+    }
+
+    #[test]
+    fn test_folding_ranges() {
+        let source = "fn main() {\n    let x = 1;\n    if x { x; }\n}\n";
+        let tree = SyntaxNodeRef::new_root(Parser::new(tokenize(source)).parse().green_node);
+
+        let ranges = folding_ranges(&tree);
+        assert_eq!(ranges.len(), 1);
+
+        let (start, end) = ranges[0];
+        let folded = &source[usize::from(start)..usize::from(end)];
+        assert!(folded.starts_with('{'));
+        assert!(folded.ends_with('}'));
+        assert_eq!(usize::from(end), source.trim_end().len());
+    }
 }
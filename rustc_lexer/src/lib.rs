@@ -9,6 +9,13 @@ pub struct Token {
     pub span: Range<usize>,
 }
 
+impl Token {
+    /// The doc-comment flavour of this token, or `None` if it isn't a comment.
+    pub fn comment_kind(&self) -> Option<CommentKind> {
+        is_comment(self.kind).then(|| comment_kind(&self.text))
+    }
+}
+
 pub struct Lexer<'input> {
     input: &'input str,
     position: usize,
@@ -51,6 +58,16 @@ impl<'input> Lexer<'input> {
         tokens
     }
 
+    /// Like `tokenize`, but for a snippet embedded at byte offset `base` of a
+    /// larger file, so spans are absolute positions in that file.
+    pub fn tokenize_with_base(&mut self, base: usize) -> Vec<Token> {
+        let mut tokens = self.tokenize();
+        for token in &mut tokens {
+            token.span = token.span.start + base..token.span.end + base;
+        }
+        tokens
+    }
+
     pub fn tokenize_with_trivia(&mut self) -> Vec<Token> {
         let mut tokens = Vec::new();
 
@@ -92,7 +109,25 @@ pub fn cook_lexer_literal(
                 return Err(LiteralError::EmptyInt);
             }
 
-            let text = text.replace('_', "");
+            let (digits, suffix) = split_int_suffix(text, base);
+            let suffix = suffix.map(str::to_string);
+            let text = digits.replace('_', "");
+
+            // `1f32` lexes as an integer but is really a float literal
+            if let Some(float_suffix) = suffix.as_deref().filter(|s| FLOAT_SUFFIXES.contains(s)) {
+                if base != Base::Decimal {
+                    return Err(LiteralError::InvalidSuffix(float_suffix.to_string()));
+                }
+                return match text.parse() {
+                    Ok(f) => Ok(ParsedLiteral::Float(f, suffix)),
+                    Err(_) => Err(LiteralError::InvalidFloat),
+                };
+            }
+
+            if let Some(suffix) = suffix.as_deref().filter(|s| !INT_SUFFIXES.contains(s)) {
+                return Err(LiteralError::InvalidSuffix(suffix.to_string()));
+            }
+
             let value = match base {
                 Base::Binary => u128::from_str_radix(&text[2..], 2),
                 Base::Octal => u128::from_str_radix(&text[2..], 8),
@@ -101,7 +136,7 @@ pub fn cook_lexer_literal(
             };
 
             match value {
-                Ok(n) => Ok(ParsedLiteral::Int(n)),
+                Ok(n) => Ok(ParsedLiteral::Int(n, suffix)),
                 Err(_) => Err(LiteralError::IntegerOverflow),
             }
         }
@@ -118,9 +153,14 @@ pub fn cook_lexer_literal(
                 return Err(LiteralError::NonDecimalFloat);
             }
 
-            let text = text.replace('_', "");
+            let (digits, suffix) = split_float_suffix(text);
+            if let Some(suffix) = suffix.filter(|s| !FLOAT_SUFFIXES.contains(s)) {
+                return Err(LiteralError::InvalidSuffix(suffix.to_string()));
+            }
+
+            let text = digits.replace('_', "");
             match text.parse() {
-                Ok(f) => Ok(ParsedLiteral::Float(f)),
+                Ok(f) => Ok(ParsedLiteral::Float(f, suffix.map(str::to_string))),
                 Err(_) => Err(LiteralError::InvalidFloat),
             }
         }
@@ -199,10 +239,44 @@ pub fn cook_lexer_literal(
     }
 }
 
+const INT_SUFFIXES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+const FLOAT_SUFFIXES: &[&str] = &["f32", "f64"];
+
+// rustc_lexer includes the type suffix in the literal text. For integers the
+// suffix starts at the first character that can't be a digit, so `0xffu8`
+// splits as `0xff` / `u8` while `0x1f32` has no suffix at all.
+fn split_int_suffix(text: &str, base: Base) -> (&str, Option<&str>) {
+    let prefix_len = if base == Base::Decimal { 0 } else { 2 };
+    let is_digit = |c: char| {
+        c == '_' || c.is_ascii_digit() || (base == Base::Hexadecimal && c.is_ascii_hexdigit())
+    };
+    match text[prefix_len..].find(|c: char| !is_digit(c)) {
+        Some(i) => {
+            let (digits, suffix) = text.split_at(prefix_len + i);
+            (digits, Some(suffix))
+        }
+        None => (text, None),
+    }
+}
+
+// A float suffix starts at the first letter other than the exponent marker.
+fn split_float_suffix(text: &str) -> (&str, Option<&str>) {
+    match text.find(|c: char| c.is_alphabetic() && c != 'e' && c != 'E') {
+        Some(i) => {
+            let (digits, suffix) = text.split_at(i);
+            (digits, Some(suffix))
+        }
+        None => (text, None),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ParsedLiteral {
-    Int(u128),
-    Float(f64),
+    Int(u128, Option<String>),
+    Float(f64, Option<String>),
     Char(char),
     Byte(u8),
     Str(String),
@@ -218,6 +292,7 @@ pub enum LiteralError {
     EmptyExponent,
     NonDecimalFloat,
     InvalidFloat,
+    InvalidSuffix(String),
     UnterminatedChar,
     UnterminatedByte,
     UnterminatedString,
@@ -227,35 +302,85 @@ pub enum LiteralError {
     InvalidEscape(String),
 }
 
-// Simplified escape handling - in real compiler this would be much more
-// comprehensive
-fn unescape_char(s: &str) -> Result<char, LiteralError> {
-    if let Some(stripped) = s.strip_prefix('\\') {
-        match stripped {
-            "n" => Ok('\n'),
-            "r" => Ok('\r'),
-            "t" => Ok('\t'),
-            "\\" => Ok('\\'),
-            "'" => Ok('\''),
-            "\"" => Ok('"'),
-            "0" => Ok('\0'),
-            _ => Err(LiteralError::InvalidEscape(s.to_string())),
+// Decodes the escape following a backslash. Byte literals accept any `\xNN`
+// but no `\u{...}`; char and string literals cap `\xNN` at 0x7F.
+fn unescape_escape(chars: &mut std::str::Chars, is_byte: bool) -> Result<u32, LiteralError> {
+    let rest = chars.as_str();
+    let invalid = |len: usize| {
+        let len = rest.char_indices().nth(len).map_or(rest.len(), |(i, _)| i);
+        LiteralError::InvalidEscape(format!("\\{}", &rest[..len]))
+    };
+
+    match chars.next() {
+        Some('n') => Ok('\n' as u32),
+        Some('r') => Ok('\r' as u32),
+        Some('t') => Ok('\t' as u32),
+        Some('\\') => Ok('\\' as u32),
+        Some('\'') => Ok('\'' as u32),
+        Some('"') => Ok('"' as u32),
+        Some('0') => Ok(0),
+        Some('x') => {
+            let hex: String = chars.by_ref().take(2).collect();
+            match parse_hex(&hex) {
+                Some(value) if hex.len() == 2 && (is_byte || value <= 0x7F) => Ok(value),
+                _ => Err(invalid(1 + hex.chars().count())),
+            }
         }
-    } else if s.len() == 1 {
-        Ok(s.chars().next().unwrap())
-    } else {
-        Err(LiteralError::InvalidEscape(s.to_string()))
+        Some('u') if !is_byte => {
+            let Some(body) = rest[1..].strip_prefix('{') else {
+                return Err(invalid(1));
+            };
+            let Some(close) = body.find('}') else {
+                return Err(invalid(rest.chars().count()));
+            };
+            let escape_len = 3 + body[..close].chars().count();
+            let hex = body[..close].replace('_', "");
+            let value = parse_hex(&hex)
+                .filter(|&v| hex.len() <= 6 && char::from_u32(v).is_some())
+                .ok_or_else(|| invalid(escape_len))?;
+            for _ in 1..escape_len {
+                chars.next();
+            }
+            Ok(value)
+        }
+        Some(_) => Err(invalid(1)),
+        None => Err(invalid(0)),
+    }
+}
+
+fn parse_hex(s: &str) -> Option<u32> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(s, 16).ok()
+}
+
+fn unescape_char(s: &str) -> Result<char, LiteralError> {
+    let mut chars = s.chars();
+    let value = match chars.next() {
+        Some('\\') => unescape_escape(&mut chars, false)?,
+        Some(c) => c as u32,
+        None => return Err(LiteralError::InvalidEscape(s.to_string())),
+    };
+
+    match char::from_u32(value) {
+        Some(c) if chars.as_str().is_empty() => Ok(c),
+        _ => Err(LiteralError::InvalidEscape(s.to_string())),
     }
 }
 
 fn unescape_byte(s: &str) -> Result<u8, LiteralError> {
-    unescape_char(s).and_then(|c| {
-        if c as u32 <= 255 {
-            Ok(c as u8)
-        } else {
-            Err(LiteralError::InvalidEscape(s.to_string()))
-        }
-    })
+    let mut chars = s.chars();
+    let value = match chars.next() {
+        Some('\\') => unescape_escape(&mut chars, true)?,
+        Some(c) => c as u32,
+        None => return Err(LiteralError::InvalidEscape(s.to_string())),
+    };
+
+    match u8::try_from(value) {
+        Ok(b) if chars.as_str().is_empty() => Ok(b),
+        _ => Err(LiteralError::InvalidEscape(s.to_string())),
+    }
 }
 
 fn unescape_string(s: &str) -> Result<String, LiteralError> {
@@ -264,18 +389,9 @@ fn unescape_string(s: &str) -> Result<String, LiteralError> {
 
     while let Some(ch) = chars.next() {
         if ch == '\\' {
-            if let Some(next) = chars.next() {
-                match next {
-                    'n' => result.push('\n'),
-                    'r' => result.push('\r'),
-                    't' => result.push('\t'),
-                    '\\' => result.push('\\'),
-                    '\'' => result.push('\''),
-                    '"' => result.push('"'),
-                    '0' => result.push('\0'),
-                    _ => return Err(LiteralError::InvalidEscape(format!("\\{}", next))),
-                }
-            }
+            let value = unescape_escape(&mut chars, false)?;
+            // unescape_escape only returns valid scalar values in this mode
+            result.extend(char::from_u32(value));
         } else {
             result.push(ch);
         }
@@ -285,10 +401,33 @@ fn unescape_string(s: &str) -> Result<String, LiteralError> {
 }
 
 fn unescape_byte_string(s: &str) -> Result<Vec<u8>, LiteralError> {
-    unescape_string(s).map(|s| s.into_bytes())
+    let mut result = Vec::new();
+    let mut chars = s.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            result.push(unescape_escape(&mut chars, true)? as u8);
+        } else {
+            let mut buf = [0; 4];
+            result.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+        }
+    }
+
+    Ok(result)
 }
 
 pub fn tokenize_and_validate(input: &str) -> Result<Vec<Token>, Vec<ValidationError>> {
+    let (tokens, errors) = tokenize_lossy(input);
+    if errors.is_empty() {
+        Ok(tokens)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Like `tokenize_and_validate`, but always hands back the full token stream
+/// alongside any errors so tooling such as highlighters can keep going.
+pub fn tokenize_lossy(input: &str) -> (Vec<Token>, Vec<ValidationError>) {
     let mut lexer = Lexer::new(input);
     let mut errors = Vec::new();
     let tokens = lexer.tokenize_with_trivia();
@@ -315,11 +454,7 @@ pub fn tokenize_and_validate(input: &str) -> Result<Vec<Token>, Vec<ValidationEr
         }
     }
 
-    if errors.is_empty() {
-        Ok(tokens)
-    } else {
-        Err(errors)
-    }
+    (tokens, errors)
 }
 
 #[derive(Debug, Clone)]
@@ -350,6 +485,14 @@ pub fn is_literal(kind: TokenKind) -> bool {
     matches!(kind, TokenKind::Literal { .. })
 }
 
+/// The identifier behind a raw identifier token, e.g. `fn` for `r#fn`.
+pub fn raw_ident_name(token: &Token) -> Option<&str> {
+    match token.kind {
+        TokenKind::RawIdent => token.text.strip_prefix("r#"),
+        _ => None,
+    }
+}
+
 pub fn describe_token(kind: TokenKind) -> &'static str {
     match kind {
         TokenKind::Ident => "identifier",
@@ -399,6 +542,252 @@ pub fn describe_token(kind: TokenKind) -> &'static str {
     }
 }
 
+/// Outline the functions in `input` as `name(params)` slices of the source,
+/// without parsing anything beyond balanced parentheses. Generic parameters
+/// between the name and the parameter list are kept in the slice.
+pub fn extract_fn_signatures(input: &str) -> Vec<String> {
+    let tokens = Lexer::new(input).tokenize();
+    let mut signatures = Vec::new();
+
+    let mut i = 0;
+    while i + 1 < tokens.len() {
+        let name = &tokens[i + 1];
+        if tokens[i].kind != TokenKind::Ident
+            || tokens[i].text != "fn"
+            || !matches!(name.kind, TokenKind::Ident | TokenKind::RawIdent)
+        {
+            i += 1;
+            continue;
+        }
+
+        // Skip over the generic parameters, whose bounds may hold their own
+        // parentheses (`F: Fn(i32) -> i32`). The `>` of an `->` doesn't
+        // close anything.
+        let mut angle_depth = 0;
+        let Some(open) = (i + 2..tokens.len())
+            .find(|&j| {
+                let t = &tokens[j];
+                match t.kind {
+                    TokenKind::Lt => angle_depth += 1,
+                    TokenKind::Gt
+                        if tokens[j - 1].kind != TokenKind::Minus
+                            || tokens[j - 1].span.end != t.span.start =>
+                    {
+                        angle_depth -= 1
+                    }
+                    _ => {}
+                }
+                angle_depth == 0
+                    && matches!(
+                        t.kind,
+                        TokenKind::OpenParen | TokenKind::OpenBrace | TokenKind::Semi
+                    )
+            })
+            .filter(|&open| tokens[open].kind == TokenKind::OpenParen)
+        else {
+            i += 2;
+            continue;
+        };
+
+        let mut depth = 0;
+        let close = tokens[open..].iter().position(|t| {
+            match t.kind {
+                TokenKind::OpenParen => depth += 1,
+                TokenKind::CloseParen => depth -= 1,
+                _ => {}
+            }
+            depth == 0
+        });
+
+        match close {
+            Some(offset) => {
+                let close = open + offset;
+                signatures.push(input[name.span.start..tokens[close].span.end].to_string());
+                i = close + 1;
+            }
+            None => break,
+        }
+    }
+
+    signatures
+}
+
+/// Re-emit the non-trivia tokens of `input` separated by single spaces,
+/// dropping all whitespace and comments. Every token boundary gets a space
+/// except inside a `{}` written without a gap, so this is a crude normaliser
+/// rather than a formatter: `a::b` becomes `a : : b`. Literals are copied
+/// verbatim, so spaces inside strings survive.
+pub fn normalize_whitespace(input: &str) -> String {
+    let tokens: Vec<Token> = Lexer::new(input)
+        .tokenize_with_trivia()
+        .into_iter()
+        .filter(|token| !is_whitespace(token.kind) && !is_comment(token.kind))
+        .collect();
+
+    let mut out = String::with_capacity(input.len());
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 {
+            let prev = &tokens[i - 1];
+            let empty_block = prev.kind == TokenKind::OpenBrace
+                && token.kind == TokenKind::CloseBrace
+                && prev.span.end == token.span.start;
+            if !empty_block {
+                out.push(' ');
+            }
+        }
+        out.push_str(&token.text);
+    }
+    out
+}
+
+/// Check that `(`, `[` and `{` are properly nested in `input`, returning the
+/// byte offset and a message for every problem found. Brackets inside string
+/// literals and comments are ignored since the lexer folds those into single
+/// tokens. A closer that doesn't match the innermost open bracket is reported
+/// and then treated as closing it, so `{ [ ) }` yields a single error.
+pub fn check_balanced(input: &str) -> Result<(), Vec<(usize, String)>> {
+    let mut stack: Vec<(char, usize)> = Vec::new();
+    let mut errors = Vec::new();
+
+    for token in Lexer::new(input).tokenize() {
+        let offset = token.span.start;
+        match token.kind {
+            TokenKind::OpenParen => stack.push(('(', offset)),
+            TokenKind::OpenBracket => stack.push(('[', offset)),
+            TokenKind::OpenBrace => stack.push(('{', offset)),
+            TokenKind::CloseParen | TokenKind::CloseBracket | TokenKind::CloseBrace => {
+                let close = token.text.chars().next().unwrap();
+                match stack.pop() {
+                    Some((open, _)) if closing_bracket(open) == close => {}
+                    Some((open, open_offset)) => errors.push((
+                        offset,
+                        format!(
+                            "mismatched `{}`: expected `{}` to close `{}` at {}",
+                            close,
+                            closing_bracket(open),
+                            open,
+                            open_offset
+                        ),
+                    )),
+                    None => errors.push((offset, format!("unmatched `{}`", close))),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    errors.extend(
+        stack
+            .into_iter()
+            .map(|(open, offset)| (offset, format!("unclosed `{}`", open))),
+    );
+    errors.sort_by_key(|&(offset, _)| offset);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn closing_bracket(open: char) -> char {
+    match open {
+        '(' => ')',
+        '[' => ']',
+        _ => '}',
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentKind {
+    /// `///` or `/** */`, documenting the item that follows
+    Outer,
+    /// `//!` or `/*! */`, documenting the enclosing item
+    Inner,
+    Regular,
+}
+
+// Follows rustc: `////` and `/***` are ordinary comments, as is the empty
+// block comment `/**/`.
+pub fn comment_kind(text: &str) -> CommentKind {
+    if text.starts_with("//!") || text.starts_with("/*!") {
+        CommentKind::Inner
+    } else if (text.starts_with("///") && !text.starts_with("////"))
+        || (text.starts_with("/**") && !text.starts_with("/***") && text != "/**/")
+    {
+        CommentKind::Outer
+    } else {
+        CommentKind::Regular
+    }
+}
+
+/// Strict and reserved keywords for the 2018+ editions.
+pub const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+pub fn is_keyword(text: &str) -> bool {
+    KEYWORDS.contains(&text)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenCategory {
+    Keyword,
+    Identifier,
+    Literal,
+    Operator,
+    Punctuation,
+    Comment,
+    Whitespace,
+    Unknown,
+}
+
+// rustc_lexer reports keywords as plain identifiers, so the token text is
+// needed to tell them apart. Raw identifiers like `r#fn` are never keywords.
+pub fn classify(kind: TokenKind, text: &str) -> TokenCategory {
+    match kind {
+        TokenKind::Ident if is_keyword(text) => TokenCategory::Keyword,
+        TokenKind::Ident | TokenKind::RawIdent | TokenKind::Lifetime { .. } => {
+            TokenCategory::Identifier
+        }
+        TokenKind::Literal { .. } => TokenCategory::Literal,
+        TokenKind::Eq
+        | TokenKind::Lt
+        | TokenKind::Gt
+        | TokenKind::Minus
+        | TokenKind::And
+        | TokenKind::Or
+        | TokenKind::Plus
+        | TokenKind::Star
+        | TokenKind::Slash
+        | TokenKind::Caret
+        | TokenKind::Percent
+        | TokenKind::Not
+        | TokenKind::Tilde
+        | TokenKind::Question => TokenCategory::Operator,
+        TokenKind::Semi
+        | TokenKind::Comma
+        | TokenKind::Dot
+        | TokenKind::OpenParen
+        | TokenKind::CloseParen
+        | TokenKind::OpenBrace
+        | TokenKind::CloseBrace
+        | TokenKind::OpenBracket
+        | TokenKind::CloseBracket
+        | TokenKind::At
+        | TokenKind::Pound
+        | TokenKind::Colon
+        | TokenKind::Dollar => TokenCategory::Punctuation,
+        TokenKind::LineComment | TokenKind::BlockComment { .. } => TokenCategory::Comment,
+        TokenKind::Whitespace => TokenCategory::Whitespace,
+        TokenKind::Unknown => TokenCategory::Unknown,
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::approx_constant)]
 mod tests {
@@ -463,7 +852,7 @@ mod tests {
                     empty_int: false,
                 },
                 "42",
-                ParsedLiteral::Int(42),
+                ParsedLiteral::Int(42, None),
             ),
             (
                 LiteralKind::Int {
@@ -471,7 +860,7 @@ mod tests {
                     empty_int: false,
                 },
                 "0xFF",
-                ParsedLiteral::Int(255),
+                ParsedLiteral::Int(255, None),
             ),
             (
                 LiteralKind::Float {
@@ -479,7 +868,7 @@ mod tests {
                     empty_exponent: false,
                 },
                 "3.14",
-                ParsedLiteral::Float(3.14),
+                ParsedLiteral::Float(3.14, None),
             ),
             (
                 LiteralKind::Char { terminated: true },
@@ -493,4 +882,335 @@ mod tests {
             assert_eq!(result, expected);
         }
     }
+
+    #[test]
+    fn test_int_suffixes() {
+        let int = |base| LiteralKind::Int {
+            base,
+            empty_int: false,
+        };
+
+        assert_eq!(
+            cook_lexer_literal(int(Base::Decimal), "255u8", 0).unwrap(),
+            ParsedLiteral::Int(255, Some("u8".to_string()))
+        );
+        assert_eq!(
+            cook_lexer_literal(int(Base::Decimal), "1_000i64", 0).unwrap(),
+            ParsedLiteral::Int(1000, Some("i64".to_string()))
+        );
+        assert_eq!(
+            cook_lexer_literal(int(Base::Hexadecimal), "0xffusize", 0).unwrap(),
+            ParsedLiteral::Int(255, Some("usize".to_string()))
+        );
+        // All hex digits, so there is no suffix to strip
+        assert_eq!(
+            cook_lexer_literal(int(Base::Hexadecimal), "0x1f32", 0).unwrap(),
+            ParsedLiteral::Int(0x1f32, None)
+        );
+        // The suffix is kept so the value can be range-checked later
+        assert_eq!(
+            cook_lexer_literal(int(Base::Decimal), "256u8", 0).unwrap(),
+            ParsedLiteral::Int(256, Some("u8".to_string()))
+        );
+        assert_eq!(
+            cook_lexer_literal(int(Base::Decimal), "7f32", 0).unwrap(),
+            ParsedLiteral::Float(7.0, Some("f32".to_string()))
+        );
+        assert_eq!(
+            cook_lexer_literal(int(Base::Decimal), "42u7", 0),
+            Err(LiteralError::InvalidSuffix("u7".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_float_suffixes() {
+        let float = LiteralKind::Float {
+            base: Base::Decimal,
+            empty_exponent: false,
+        };
+
+        assert_eq!(
+            cook_lexer_literal(float, "3.14f32", 0).unwrap(),
+            ParsedLiteral::Float(3.14, Some("f32".to_string()))
+        );
+        assert_eq!(
+            cook_lexer_literal(float, "2.5e-3_f64", 0).unwrap(),
+            ParsedLiteral::Float(2.5e-3, Some("f64".to_string()))
+        );
+        assert_eq!(
+            cook_lexer_literal(float, "1E10", 0).unwrap(),
+            ParsedLiteral::Float(1e10, None)
+        );
+        assert_eq!(
+            cook_lexer_literal(float, "1.0u8", 0),
+            Err(LiteralError::InvalidSuffix("u8".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_suffix_from_lexer() {
+        let mut lexer = Lexer::new("255u8 3.14f32");
+        let tokens = lexer.tokenize();
+        let parsed: Vec<_> = tokens
+            .iter()
+            .map(|token| match token.kind {
+                TokenKind::Literal { kind, .. } => {
+                    cook_lexer_literal(kind, &token.text, 0).unwrap()
+                }
+                _ => panic!("Expected literal"),
+            })
+            .collect();
+
+        assert_eq!(
+            parsed,
+            vec![
+                ParsedLiteral::Int(255, Some("u8".to_string())),
+                ParsedLiteral::Float(3.14, Some("f32".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unicode_escapes() {
+        let char_kind = LiteralKind::Char { terminated: true };
+        let str_kind = LiteralKind::Str { terminated: true };
+
+        assert_eq!(
+            cook_lexer_literal(char_kind, r"'\u{41}'", 0).unwrap(),
+            ParsedLiteral::Char('A')
+        );
+        assert_eq!(
+            cook_lexer_literal(str_kind, r#""smile \u{1F600}!""#, 0).unwrap(),
+            ParsedLiteral::Str("smile \u{1F600}!".to_string())
+        );
+        assert_eq!(
+            cook_lexer_literal(str_kind, r#""\u{10_FFFF}\x41""#, 0).unwrap(),
+            ParsedLiteral::Str("\u{10FFFF}A".to_string())
+        );
+
+        assert_eq!(
+            cook_lexer_literal(str_kind, r#""\u{110000}""#, 0),
+            Err(LiteralError::InvalidEscape(r"\u{110000}".to_string()))
+        );
+        assert_eq!(
+            cook_lexer_literal(char_kind, r"'\u{D800}'", 0),
+            Err(LiteralError::InvalidEscape(r"\u{D800}".to_string()))
+        );
+        assert_eq!(
+            cook_lexer_literal(str_kind, r#""\u41""#, 0),
+            Err(LiteralError::InvalidEscape(r"\u".to_string()))
+        );
+        // Only ASCII is reachable through \x outside of byte literals
+        assert_eq!(
+            cook_lexer_literal(char_kind, r"'\xFF'", 0),
+            Err(LiteralError::InvalidEscape(r"\xFF".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_byte_escapes() {
+        assert_eq!(
+            cook_lexer_literal(LiteralKind::Byte { terminated: true }, r"b'\xFF'", 0).unwrap(),
+            ParsedLiteral::Byte(0xFF)
+        );
+        assert_eq!(
+            cook_lexer_literal(
+                LiteralKind::ByteStr { terminated: true },
+                r#"b"\x00a\x7f\n""#,
+                0
+            )
+            .unwrap(),
+            ParsedLiteral::ByteStr(vec![0x00, b'a', 0x7F, b'\n'])
+        );
+        assert_eq!(
+            cook_lexer_literal(LiteralKind::ByteStr { terminated: true }, r#"b"\u{41}""#, 0),
+            Err(LiteralError::InvalidEscape(r"\u".to_string()))
+        );
+        assert_eq!(
+            cook_lexer_literal(LiteralKind::ByteStr { terminated: true }, r#"b"\x+1""#, 0),
+            Err(LiteralError::InvalidEscape(r"\x+1".to_string()))
+        );
+        assert_eq!(
+            cook_lexer_literal(LiteralKind::Byte { terminated: true }, r"b'\xG1'", 0),
+            Err(LiteralError::InvalidEscape(r"\xG1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_classify() {
+        let input = "fn foo() { match x { _ => 1 + 2 } } // done\n r#match 'a €";
+        let mut lexer = Lexer::new(input);
+        let categories: Vec<_> = lexer
+            .tokenize_with_trivia()
+            .iter()
+            .map(|t| (t.text.clone(), classify(t.kind, &t.text)))
+            .collect();
+        let category_of = |text: &str| {
+            categories
+                .iter()
+                .find(|(t, _)| t == text)
+                .map(|(_, c)| *c)
+                .unwrap()
+        };
+
+        assert_eq!(category_of("fn"), TokenCategory::Keyword);
+        assert_eq!(category_of("match"), TokenCategory::Keyword);
+        assert_eq!(category_of("foo"), TokenCategory::Identifier);
+        assert_eq!(category_of("r#match"), TokenCategory::Identifier);
+        assert_eq!(category_of("'a"), TokenCategory::Identifier);
+        assert_eq!(category_of("1"), TokenCategory::Literal);
+        assert_eq!(category_of("+"), TokenCategory::Operator);
+        assert_eq!(category_of("="), TokenCategory::Operator);
+        assert_eq!(category_of("("), TokenCategory::Punctuation);
+        assert_eq!(category_of("{"), TokenCategory::Punctuation);
+        assert_eq!(category_of("// done"), TokenCategory::Comment);
+        assert_eq!(category_of(" "), TokenCategory::Whitespace);
+        assert_eq!(category_of("€"), TokenCategory::Unknown);
+    }
+
+    #[test]
+    fn test_comment_kind() {
+        assert_eq!(comment_kind("//! crate docs"), CommentKind::Inner);
+        assert_eq!(comment_kind("/// item docs"), CommentKind::Outer);
+        assert_eq!(comment_kind("// plain"), CommentKind::Regular);
+        assert_eq!(comment_kind("//// not docs"), CommentKind::Regular);
+
+        assert_eq!(comment_kind("/*! crate docs */"), CommentKind::Inner);
+        assert_eq!(comment_kind("/** item docs */"), CommentKind::Outer);
+        assert_eq!(comment_kind("/* plain */"), CommentKind::Regular);
+        assert_eq!(comment_kind("/*** not docs */"), CommentKind::Regular);
+        assert_eq!(comment_kind("/**/"), CommentKind::Regular);
+
+        let mut lexer = Lexer::new("//! inner\n/// outer\nfn f() {}");
+        let kinds: Vec<_> = lexer
+            .tokenize_with_trivia()
+            .iter()
+            .filter_map(Token::comment_kind)
+            .collect();
+        assert_eq!(kinds, vec![CommentKind::Inner, CommentKind::Outer]);
+    }
+
+    #[test]
+    fn test_tokenize_lossy() {
+        let input = "let x = 1 § 2;";
+        assert!(tokenize_and_validate(input).is_err());
+
+        let (tokens, errors) = tokenize_lossy(input);
+        let texts: Vec<_> = tokens
+            .iter()
+            .filter(|t| !is_whitespace(t.kind))
+            .map(|t| t.text.as_str())
+            .collect();
+        assert_eq!(texts, vec!["let", "x", "=", "1", "§", "2", ";"]);
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ValidationErrorKind::UnknownToken));
+        assert_eq!(&input[errors[0].span.clone()], "§");
+        assert_eq!(tokens[errors[0].token_index].kind, TokenKind::Unknown);
+    }
+
+    #[test]
+    fn test_raw_ident_name() {
+        let mut lexer = Lexer::new("let r#type = r#fn;");
+        let tokens = lexer.tokenize();
+
+        let raw: Vec<_> = tokens.iter().filter_map(raw_ident_name).collect();
+        assert_eq!(raw, vec!["type", "fn"]);
+        assert_eq!(raw_ident_name(&tokens[0]), None);
+    }
+
+    #[test]
+    fn test_extract_fn_signatures() {
+        let source = r#"
+            // fn commented_out(x: i32)
+            pub fn pairs(x: Vec<(i32, i32)>, f: fn(i32) -> i32) -> usize {
+                x.len()
+            }
+
+            fn generic<T: Clone>(value: &T) -> T { value.clone() }
+
+            fn apply<F: Fn(i32) -> i32>(f: F) -> i32 { f(1) }
+        "#;
+
+        assert_eq!(
+            extract_fn_signatures(source),
+            vec![
+                "pairs(x: Vec<(i32, i32)>, f: fn(i32) -> i32)",
+                "generic<T: Clone>(value: &T)",
+                "apply<F: Fn(i32) -> i32>(f: F)",
+            ]
+        );
+        assert!(extract_fn_signatures("fn unterminated(x: i32").is_empty());
+    }
+
+    #[test]
+    fn test_tokenize_with_base() {
+        let snippet = "let x = 1;";
+        let local = Lexer::new(snippet).tokenize();
+        let shifted = Lexer::new(snippet).tokenize_with_base(100);
+
+        assert_eq!(local.len(), shifted.len());
+        for (a, b) in local.iter().zip(&shifted) {
+            assert_eq!(a.kind, b.kind);
+            assert_eq!(a.text, b.text);
+            assert_eq!(a.span.start + 100, b.span.start);
+            assert_eq!(a.span.end + 100, b.span.end);
+        }
+
+        // Two snippets placed back to back index into the combined source
+        let first = "fn a() {}";
+        let second = "fn b() {}";
+        let combined = format!("{}{}", first, second);
+        let mut tokens = Lexer::new(first).tokenize_with_base(0);
+        tokens.extend(Lexer::new(second).tokenize_with_base(first.len()));
+        assert_eq!(tokens, Lexer::new(&combined).tokenize());
+        for token in &tokens {
+            assert_eq!(&combined[token.span.clone()], token.text);
+        }
+    }
+
+    #[test]
+    fn test_normalize_whitespace() {
+        assert_eq!(normalize_whitespace("fn   main( )  {}"), "fn main ( ) {}");
+        // Only a brace pair that touches in the source stays joined
+        assert_eq!(normalize_whitespace("{ }"), "{ }");
+        assert_eq!(
+            normalize_whitespace("  let s =\n\t\"a   b\"; // trailing\n"),
+            "let s = \"a   b\" ;"
+        );
+        assert_eq!(normalize_whitespace("a/* gap */b"), "a b");
+    }
+
+    #[test]
+    fn test_check_balanced() {
+        assert_eq!(check_balanced("fn f(a: [u8; 2]) { g(a[0]) }"), Ok(()));
+        // Brackets in strings and comments don't count
+        assert_eq!(check_balanced("let s = \"(\"; // ]\n/* { */ x"), Ok(()));
+    }
+
+    #[test]
+    fn test_check_balanced_unmatched() {
+        assert_eq!(
+            check_balanced("f(a, { b"),
+            Err(vec![
+                (1, "unclosed `(`".to_string()),
+                (5, "unclosed `{`".to_string()),
+            ])
+        );
+        assert_eq!(
+            check_balanced("x) y"),
+            Err(vec![(1, "unmatched `)`".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_check_balanced_mismatched() {
+        assert_eq!(
+            check_balanced("{ [ ) }"),
+            Err(vec![(
+                4,
+                "mismatched `)`: expected `]` to close `[` at 2".to_string()
+            )])
+        );
+    }
 }
@@ -1,7 +1,7 @@
 use rustc_lexer::{LiteralKind, TokenKind};
 use rustc_lexer_example::{
-    cook_lexer_literal, describe_token, is_comment, is_whitespace, tokenize_and_validate, Lexer,
-    ParsedLiteral,
+    classify, cook_lexer_literal, describe_token, is_comment, is_whitespace, raw_ident_name,
+    tokenize_and_validate, tokenize_lossy, CommentKind, Lexer, ParsedLiteral, TokenCategory,
 };
 
 fn main() {
@@ -42,6 +42,10 @@ fn calculate(x: i32, y: f64) -> f64 {
     println!("=== Error Detection ===");
     demonstrate_error_handling();
 
+    println!();
+    println!("=== Lossy Tokenization ===");
+    demonstrate_lossy_tokenization();
+
     println!();
     println!("=== Advanced Token Kinds ===");
     demonstrate_advanced_tokens();
@@ -57,10 +61,14 @@ fn demonstrate_literals() {
 0xFF            // hexadecimal
 0o77            // octal
 0b1010          // binary
+255u8           // suffixed integer
 3.14            // float
 2.71e-10        // scientific notation
+1.5f32          // suffixed float
 'a'             // character
 '\n'            // escaped character
+'\u{1F600}'      // unicode escape
+b'\xFF'         // byte escape
 "hello"         // string
 b"bytes"        // byte string
 b'A'            // byte literal
@@ -200,6 +208,19 @@ fn demonstrate_error_handling() {
     }
 }
 
+fn demonstrate_lossy_tokenization() {
+    let code = "let total = price § tax;";
+    let (tokens, errors) = tokenize_lossy(code);
+
+    println!("Tokens recovered despite errors:");
+    for token in tokens.iter().filter(|t| !is_whitespace(t.kind)) {
+        println!("  {:8} => {}", token.text, describe_token(token.kind));
+    }
+    for error in errors {
+        println!("  Error at {:?}: {:?}", error.span, error.kind);
+    }
+}
+
 fn demonstrate_advanced_tokens() {
     let advanced = r#"
 'static
@@ -227,7 +248,11 @@ Self::Associated
                 );
             }
             TokenKind::RawIdent => {
-                println!("  Raw identifier: {}", token.text);
+                println!(
+                    "  Raw identifier: {} (name: {})",
+                    token.text,
+                    raw_ident_name(token).unwrap_or_default()
+                );
             }
             TokenKind::Dollar => {
                 println!("  Dollar sign (macro metavariable): {}", token.text);
@@ -287,26 +312,15 @@ impl<'input> Lexer<'input> {
         match token.kind {
             TokenKind::Ident => {
                 stats.identifiers += 1;
-                match token.text.as_str() {
-                    "pub" | "struct" | "impl" | "fn" | "let" | "match" | "while" => {
-                        stats.keywords += 1
-                    }
-                    _ => {}
+                if classify(token.kind, &token.text) == TokenCategory::Keyword {
+                    stats.keywords += 1;
                 }
             }
             TokenKind::Lifetime { .. } => stats.lifetimes += 1,
             TokenKind::Literal { .. } => stats.literals += 1,
-            TokenKind::LineComment => {
-                stats.comments += 1;
-                // Check if it's a doc comment by examining the text
-                if token.text.starts_with("///") || token.text.starts_with("//!") {
-                    stats.doc_comments += 1;
-                }
-            }
-            TokenKind::BlockComment { terminated: _ } => {
+            TokenKind::LineComment | TokenKind::BlockComment { .. } => {
                 stats.comments += 1;
-                // Check if it's a doc comment by examining the text
-                if token.text.starts_with("/**") || token.text.starts_with("/*!") {
+                if token.comment_kind() != Some(CommentKind::Regular) {
                     stats.doc_comments += 1;
                 }
             }
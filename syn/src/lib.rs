@@ -10,9 +10,11 @@ use quote::quote;
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::visit_mut::{self, VisitMut};
 use syn::{
-    parse_quote, Error, Expr, ExprLit, FnArg, ItemFn, Lit, Pat, Result, Stmt, Token, Type,
-    Visibility,
+    parse_quote, BinOp, Error, Expr, ExprLit, ExprPath, FieldPat, FieldValue, FnArg, Item, ItemFn,
+    Lit, Member, Pat, PatIdent, Result, Stmt, Token, Type, Visibility,
 };
 
 /// Example: Parsing and analyzing a Rust function
@@ -407,6 +409,104 @@ pub fn const_fold_binary_ops(expr: Expr) -> Expr {
     }
 }
 
+/// Example: Rename value identifiers with a visitor
+///
+/// Rewrites every identifier bound by a pattern (parameters, `let`s, closure
+/// arguments) and every single-segment path expression for which `rename`
+/// returns a new name. Types, field names, method names and the function's
+/// own name are left alone, as are tokens inside macro invocations.
+pub fn rename_idents(mut func: ItemFn, rename: impl Fn(&str) -> Option<String>) -> ItemFn {
+    IdentRenamer { rename }.visit_item_fn_mut(&mut func);
+    func
+}
+
+struct IdentRenamer<F> {
+    rename: F,
+}
+
+impl<F: Fn(&str) -> Option<String>> IdentRenamer<F> {
+    fn renamed(&self, ident: &Ident) -> Option<Ident> {
+        (self.rename)(&ident.to_string()).map(|name| Ident::new(&name, ident.span()))
+    }
+
+    fn renames_member(&self, member: &Member) -> bool {
+        matches!(member, Member::Named(name) if self.renamed(name).is_some())
+    }
+}
+
+impl<F: Fn(&str) -> Option<String>> VisitMut for IdentRenamer<F> {
+    fn visit_pat_ident_mut(&mut self, pat: &mut PatIdent) {
+        if let Some(ident) = self.renamed(&pat.ident) {
+            pat.ident = ident;
+        }
+        visit_mut::visit_pat_ident_mut(self, pat);
+    }
+
+    // Only a bare `x` can name a local; `a::b` or `Vec::<T>::new` cannot
+    fn visit_expr_path_mut(&mut self, expr: &mut ExprPath) {
+        if expr.qself.is_none() {
+            if let Some(ident) = expr.path.get_ident().and_then(|ident| self.renamed(ident)) {
+                expr.path = ident.into();
+            }
+        }
+    }
+
+    // A shorthand field `S { x }` needs a colon once `x` is renamed
+    fn visit_field_value_mut(&mut self, field: &mut FieldValue) {
+        if field.colon_token.is_none() && self.renames_member(&field.member) {
+            field.colon_token = Some(Default::default());
+        }
+        visit_mut::visit_field_value_mut(self, field);
+    }
+
+    fn visit_field_pat_mut(&mut self, field: &mut FieldPat) {
+        if field.colon_token.is_none() && self.renames_member(&field.member) {
+            field.colon_token = Some(Default::default());
+        }
+        visit_mut::visit_field_pat_mut(self, field);
+    }
+}
+
+/// Example: McCabe cyclomatic complexity with a read-only visitor
+///
+/// Starts at 1 for the straight-line path and adds one per decision point:
+/// each `if` (including `else if` and match guards), `while`, `for`, `&&`,
+/// `||` and `?`, plus one per `match` arm beyond the first. Nested items such
+/// as inner functions are measured separately and don't count here.
+pub fn cyclomatic_complexity(func: &ItemFn) -> u32 {
+    let mut counter = ComplexityCounter { decisions: 0 };
+    counter.visit_block(&func.block);
+    counter.decisions + 1
+}
+
+struct ComplexityCounter {
+    decisions: u32,
+}
+
+impl<'ast> Visit<'ast> for ComplexityCounter {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        match expr {
+            Expr::If(_) | Expr::While(_) | Expr::ForLoop(_) | Expr::Try(_) => self.decisions += 1,
+            Expr::Binary(binary) if matches!(binary.op, BinOp::And(_) | BinOp::Or(_)) => {
+                self.decisions += 1
+            }
+            Expr::Match(expr_match) => {
+                let arms = expr_match.arms.len() as u32;
+                let guards = expr_match
+                    .arms
+                    .iter()
+                    .filter(|arm| arm.guard.is_some())
+                    .count() as u32;
+                self.decisions += arms.saturating_sub(1) + guards;
+            }
+            _ => {}
+        }
+        visit::visit_expr(self, expr);
+    }
+
+    fn visit_item(&mut self, _item: &'ast Item) {}
+}
+
 /// Error handling with span information
 pub fn validate_function(func: &ItemFn) -> std::result::Result<(), Vec<Error>> {
     let mut errors = Vec::new();
@@ -554,4 +654,76 @@ mod tests {
         assert!(types["data"].is_reference);
         assert!(types["data"].is_mutable);
     }
+
+    #[test]
+    fn test_rename_idents() {
+        let func: ItemFn = parse_quote! {
+            fn scale(x: i32, factor: i32) -> Point {
+                let y = x * factor;
+                let Point { x, .. } = Point { x, y };
+                x.abs() + y
+            }
+        };
+
+        let renamed = rename_idents(func, |name| (name == "x").then(|| "renamed_x".to_string()));
+        let expected: ItemFn = parse_quote! {
+            fn scale(renamed_x: i32, factor: i32) -> Point {
+                let y = renamed_x * factor;
+                let Point { x: renamed_x, .. } = Point { x: renamed_x, y };
+                renamed_x.abs() + y
+            }
+        };
+        assert_eq!(quote!(#renamed).to_string(), quote!(#expected).to_string());
+
+        // Type paths that happen to match are left untouched
+        let func: ItemFn = parse_quote! {
+            fn make(T: T) -> T { T::default(); T }
+        };
+        let renamed = rename_idents(func, |name| (name == "T").then(|| "t".to_string()));
+        let expected: ItemFn = parse_quote! {
+            fn make(t: T) -> T { T::default(); t }
+        };
+        assert_eq!(quote!(#renamed).to_string(), quote!(#expected).to_string());
+    }
+
+    #[test]
+    fn test_cyclomatic_complexity() {
+        let func: ItemFn = parse_quote! {
+            fn add(a: i32, b: i32) -> i32 {
+                let sum = a + b;
+                sum
+            }
+        };
+        assert_eq!(cyclomatic_complexity(&func), 1);
+
+        let func: ItemFn = parse_quote! {
+            fn classify(x: i32, verbose: bool) -> &'static str {
+                if verbose {
+                    println!("classifying {}", x);
+                }
+                let label = match x % 3 {
+                    0 => "fizz",
+                    1 => "one",
+                    _ => "other",
+                };
+                if x < 0 {
+                    return "negative";
+                }
+                label
+            }
+        };
+        // 1 + two `if`s + the two `match` arms after the first
+        assert_eq!(cyclomatic_complexity(&func), 5);
+
+        let func: ItemFn = parse_quote! {
+            fn parse(s: &str) -> Result<i32, std::num::ParseIntError> {
+                fn helper(b: bool) -> bool { if b { !b } else { b } }
+                let n = s.trim().parse::<i32>()?;
+                while n > 0 && helper(n % 2 == 0) {}
+                Ok(n)
+            }
+        };
+        // `?`, `while` and `&&`; the inner function is not counted
+        assert_eq!(cyclomatic_complexity(&func), 4);
+    }
 }
@@ -1,4 +1,7 @@
-use winnow_example::{parse_config, parse_expression, parse_json, parse_sexpr, parse_url};
+use winnow_example::{
+    parse_config, parse_expression, parse_json, parse_json_located, parse_sexpr, parse_sexpr_ref,
+    parse_url,
+};
 
 fn main() {
     println!("=== Winnow Parser Examples ===\n");
@@ -13,6 +16,9 @@ fn main() {
         "(1 + 2) * 3",
         "10 - 5 / 2",
         "100 / 10 + 5 * 2",
+        "-3.5 * 2",
+        "1e3 + 2.0e-3",
+        "1.2.3",
     ];
 
     for expr_str in &expressions {
@@ -31,6 +37,7 @@ fn main() {
         "true",
         "42",
         r#""hello world""#,
+        r#""caf\u00e9 \ud83d\ude00""#,
         "[1, 2, 3]",
         r#"{"name": "Alice", "age": 30, "active": true}"#,
     ];
@@ -49,6 +56,18 @@ fn main() {
         }
     }
 
+    // JSON errors with byte offsets
+    println!("\nJSON Error Locations:");
+    for json_str in &[r#"{"a":}"#, r#"{"a" 1}"#, "[1, 2"] {
+        match parse_json_located(json_str) {
+            Ok(json) => println!("  {} -> {:?}", json_str, json),
+            Err((offset, msg)) => {
+                println!("  {}", json_str);
+                println!("  {}^ offset {}: {}", " ".repeat(offset), offset, msg);
+            }
+        }
+    }
+
     // S-Expression parsing
     println!("\nS-Expression Parsing:");
     let sexpr_inputs = vec![
@@ -59,6 +78,8 @@ fn main() {
         "(+ 1 2)",
         "(define (square x) (* x x))",
         "(lambda (x y) (+ x y))",
+        "'(1 2 3)",
+        "(key . value)",
     ];
 
     for sexpr_str in &sexpr_inputs {
@@ -70,6 +91,14 @@ fn main() {
         }
     }
 
+    // Borrowed S-Expression parsing
+    println!("\nBorrowed S-Expression Parsing:");
+    let source = "(greet \"world\" 'politely)";
+    match parse_sexpr_ref(source) {
+        Ok(sexpr) => println!("  {} -> {:?}", source, sexpr),
+        Err(e) => println!("  Error parsing S-expression: {}", e),
+    }
+
     // Configuration parsing
     println!("\nConfiguration File Parsing:");
     let config_input = r#"server_name = "production"
@@ -77,6 +106,8 @@ port = 8080
 enable_ssl = true
 allowed_hosts = ["example.com", "www.example.com"]
 max_connections = 1000
+"log-level" = "info"
+motd = "say \"hello\""
 "#;
 
     match parse_config(config_input) {
@@ -96,6 +127,7 @@ max_connections = 1000
         "https://api.example.com:8080/v1/users",
         "http://example.com/search?q=rust&limit=10",
         "https://docs.rust-lang.org/book/ch01-00-introduction.html#getting-started",
+        "http://user:secret@[::1]:8080/admin",
     ];
 
     for url_str in &urls {
@@ -103,6 +135,9 @@ max_connections = 1000
             Ok(url) => {
                 println!("  {}", url_str);
                 println!("    Scheme: {}", url.scheme);
+                if let Some(userinfo) = &url.userinfo {
+                    println!("    Userinfo: {}", userinfo);
+                }
                 println!("    Host: {}", url.host);
                 if let Some(port) = url.port {
                     println!("    Port: {}", port);